@@ -0,0 +1,4773 @@
+use meval::tokenizer::{Operation, Token};
+use meval::{Context, ContextProvider, Expr};
+use num_rational::Ratio;
+use num_traits::{CheckedAdd, CheckedDiv, CheckedMul};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::f64::consts::{E, FRAC_PI_2, PI, SQRT_2};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+// =============================================================================
+// CONFIGURATION
+// =============================================================================
+const INF_THRESHOLD: f64 = 1e12;
+const ZERO_THRESHOLD: f64 = 1e-9;
+const DERIVATIVE_H: f64 = 1e-8;
+const BRENT_TOLERANCE: f64 = 1e-9;
+const MAX_BRENT_ITERATIONS: usize = 100;
+const MIN_VALID_FRACTION: f64 = 0.02;
+/// Most distinct values a range may have and still be reported as a FiniteSet
+const MAX_FINITE_LEVELS: usize = 20;
+
+/// Where and how finely the solver scans for poles, and how close sampled
+/// values must be to count as equal. Both value tolerances are relative to the
+/// scale of the output, so multiplying a function by a constant doesn't change
+/// what is detected.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridConfig {
+    /// Poles are looked for in [-pole_scan_radius, pole_scan_radius]
+    pub pole_scan_radius: f64,
+    /// Spacing of the pole scan's test points
+    pub pole_scan_step: f64,
+    /// Sampled values within this fraction of the output magnitude are one
+    /// level, so a function this flat is reported as a constant
+    pub level_tolerance: f64,
+    /// Holes in the sampled values narrower than this fraction of the output
+    /// scale aren't considered as range gaps
+    pub gap_tolerance: f64,
+    /// Numerical tolerances of the limit, derivative and Brent steps
+    pub solver: SolverConfig,
+}
+
+impl Default for GridConfig {
+    fn default() -> Self {
+        GridConfig {
+            pole_scan_radius: 10.0,
+            pole_scan_step: 0.05,
+            level_tolerance: 1e-9,
+            gap_tolerance: 0.05,
+            solver: SolverConfig::default(),
+        }
+    }
+}
+
+/// Accuracy against speed for the numerical steps. Lower `derivative_h` and
+/// raise `max_brent_iterations` for high-precision runs; do the opposite for
+/// fast previews. The defaults are the module constants.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolverConfig {
+    /// Sampled values beyond +/- this read as unbounded
+    pub inf_threshold: f64,
+    /// Range ends closer to 0 than this are 0
+    pub zero_threshold: f64,
+    /// Central-difference step for derivatives, relative to 1 + |x|
+    pub derivative_h: f64,
+    /// Relative x tolerance at which Brent's method stops
+    pub brent_tolerance: f64,
+    /// Iteration cap for Brent's method
+    pub max_brent_iterations: usize,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        SolverConfig {
+            inf_threshold: INF_THRESHOLD,
+            zero_threshold: ZERO_THRESHOLD,
+            derivative_h: DERIVATIVE_H,
+            brent_tolerance: BRENT_TOLERANCE,
+            max_brent_iterations: MAX_BRENT_ITERATIONS,
+        }
+    }
+}
+
+// =============================================================================
+// SYMBOLIC FORMATTING - Convert decimals to symbolic representations
+// =============================================================================
+
+/// Turns the numbers in a result (bounds, excluded points) into the symbols
+/// shown to the user. `DefaultFormatter` knows pi, E, small surds and simple
+/// fractions; a context with its own constants (c in physics, percentages in
+/// finance) implements this and falls back on `DefaultFormatter` for the rest.
+pub trait SymbolFormatter {
+    fn format(&self, val: f64) -> String;
+}
+
+/// The built-in symbol set used by every `Display` impl
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultFormatter;
+
+impl SymbolFormatter for DefaultFormatter {
+    fn format(&self, val: f64) -> String {
+        format_symbolic(val)
+    }
+}
+
+/// Try to convert a floating point to a nice symbolic string
+fn format_symbolic(val: f64) -> String {
+    if val == f64::INFINITY || val > INF_THRESHOLD {
+        return "oo".to_string();
+    }
+    if val == f64::NEG_INFINITY || val < -INF_THRESHOLD {
+        return "-oo".to_string();
+    }
+    if val.abs() < ZERO_THRESHOLD {
+        return "0".to_string();
+    }
+
+    // Check for common symbolic values
+    // Pi and multiples
+    if (val - PI).abs() < 1e-8 { return "pi".to_string(); }
+    if (val + PI).abs() < 1e-8 { return "-pi".to_string(); }
+    if (val - PI / 2.0).abs() < 1e-8 { return "pi/2".to_string(); }
+    if (val + PI / 2.0).abs() < 1e-8 { return "-pi/2".to_string(); }
+    if (val - PI / 3.0).abs() < 1e-8 { return "pi/3".to_string(); }
+    if (val - PI / 4.0).abs() < 1e-8 { return "pi/4".to_string(); }
+    if (val - PI / 6.0).abs() < 1e-8 { return "pi/6".to_string(); }
+    if (val - 2.0 * PI).abs() < 1e-8 { return "2*pi".to_string(); }
+    if (val + 2.0 * PI).abs() < 1e-8 { return "-2*pi".to_string(); }
+    
+    // e and related
+    if (val - E).abs() < 1e-8 { return "E".to_string(); }
+    if (val - 1.0/E).abs() < 1e-8 { return "exp(-1)".to_string(); }
+    if (val + 1.0/E).abs() < 1e-8 { return "-exp(-1)".to_string(); }
+    
+    // sqrt(2) and related
+    if (val - SQRT_2).abs() < 1e-8 { return "sqrt(2)".to_string(); }
+    if (val + SQRT_2).abs() < 1e-8 { return "-sqrt(2)".to_string(); }
+    if (val - SQRT_2 / 2.0).abs() < 1e-8 { return "sqrt(2)/2".to_string(); }
+    if (val + SQRT_2 / 2.0).abs() < 1e-8 { return "-sqrt(2)/2".to_string(); }
+    
+    // sqrt(3) and related
+    let sqrt3 = 3.0_f64.sqrt();
+    if (val - sqrt3).abs() < 1e-8 { return "sqrt(3)".to_string(); }
+    if (val + sqrt3).abs() < 1e-8 { return "-sqrt(3)".to_string(); }
+    if (val - sqrt3 / 2.0).abs() < 1e-8 { return "sqrt(3)/2".to_string(); }
+    
+    // x^x minimum = e^(-1/e) ~ 0.6922
+    let x_x_min = (-1.0/E).exp();
+    if (val - x_x_min).abs() < 1e-6 { return "exp(-exp(-1))".to_string(); }
+    
+    // x*exp(-x^2) extrema = +/- 1/(sqrt(2*e))
+    let x_exp_bound = (0.5_f64 / E).sqrt();
+    if (val - x_exp_bound).abs() < 1e-6 { return "1/sqrt(2*E)".to_string(); }
+    if (val + x_exp_bound).abs() < 1e-6 { return "-1/sqrt(2*E)".to_string(); }
+    
+    // x^2*exp(-x) maximum = 4/e^2 at x = 2
+    if (val - 4.0 / (E * E)).abs() < 1e-6 { return "4*exp(-2)".to_string(); }
+    
+    // ln(x)/x^p maximum = 1/(p*e) at x = e^(1/p): p = 2 and p = 1/2 (p = 1 is exp(-1) above)
+    if (val - 0.5 / E).abs() < 1e-6 { return "exp(-1)/2".to_string(); }
+    if (val - 2.0 / E).abs() < 1e-6 { return "2*exp(-1)".to_string(); }
+
+    // Try to convert to simple fraction
+    if let Some(frac) = try_to_fraction(val) {
+        return frac;
+    }
+
+    // Default: format as decimal
+    let rounded = (val * 1_000_000.0).round() / 1_000_000.0;
+    let s = format!("{:.6}", rounded);
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// Try to convert a float to a simple fraction string
+fn try_to_fraction(val: f64) -> Option<String> {
+    // Only try for reasonable values
+    if val.abs() > 1000.0 || val.abs() < 1e-6 {
+        return None;
+    }
+    
+    // Check common simple fractions
+    let fractions = [
+        (1, 2), (1, 3), (1, 4), (1, 5), (1, 6), (1, 8), (1, 10),
+        (2, 3), (3, 4), (2, 5), (3, 5), (4, 5),
+        (5, 6), (5, 8), (3, 8), (7, 8),
+    ];
+    
+    for (num, den) in fractions {
+        let frac_val = num as f64 / den as f64;
+        if (val - frac_val).abs() < 1e-9 {
+            return Some(format!("{}/{}", num, den));
+        }
+        if (val + frac_val).abs() < 1e-9 {
+            return Some(format!("-{}/{}", num, den));
+        }
+    }
+    
+    // Try using Ratio for more complex fractions
+    if let Some(ratio) = float_to_ratio(val, 1000) {
+        let (n, d) = (ratio.numer(), ratio.denom());
+        if *d != 1 && *d <= 100 && n.abs() <= 100 {
+            return Some(format!("{}/{}", n, d));
+        } else if *d == 1 {
+            return Some(format!("{}", n));
+        }
+    }
+    
+    None
+}
+
+/// Convert float to rational approximation
+fn float_to_ratio(val: f64, max_denom: i64) -> Option<Ratio<i64>> {
+    if !val.is_finite() {
+        return None;
+    }
+    
+    let sign = if val < 0.0 { -1 } else { 1 };
+    let val = val.abs();
+    
+    // Continued fraction approximation
+    let mut best_num = val.round() as i64;
+    let mut best_den = 1_i64;
+    let mut best_err = (val - best_num as f64).abs();
+    
+    for d in 1..=max_denom {
+        let n = (val * d as f64).round() as i64;
+        let err = (val - n as f64 / d as f64).abs();
+        if err < best_err {
+            best_err = err;
+            best_num = n;
+            best_den = d;
+        }
+        if err < 1e-12 {
+            break;
+        }
+    }
+    
+    if best_err < 1e-9 {
+        Some(Ratio::new(sign * best_num, best_den))
+    } else {
+        None
+    }
+}
+
+// =============================================================================
+// INTERVALS - One connected piece of the real line
+// =============================================================================
+
+/// Interval from `lo` to `hi`, each end open or closed. Infinite ends are open.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Interval {
+    pub lo: f64,
+    pub hi: f64,
+    pub lo_open: bool,
+    pub hi_open: bool,
+}
+
+impl Interval {
+    pub const fn new(lo: f64, hi: f64, lo_open: bool, hi_open: bool) -> Interval {
+        Interval { lo, hi, lo_open, hi_open }
+    }
+
+    pub const fn reals() -> Interval {
+        Interval::new(f64::NEG_INFINITY, f64::INFINITY, true, true)
+    }
+
+    pub fn contains(&self, x: f64) -> bool {
+        (x > self.lo || (!self.lo_open && x == self.lo)) && (x < self.hi || (!self.hi_open && x == self.hi))
+    }
+
+    /// Length: 0 for a single point, infinite when an end is
+    pub fn measure(&self) -> f64 {
+        self.hi - self.lo
+    }
+
+    /// Common part, None if nothing is left
+    pub fn intersect(&self, other: &Interval) -> Option<Interval> {
+        let (lo, lo_open) = if self.lo > other.lo { (self.lo, self.lo_open) }
+            else if other.lo > self.lo { (other.lo, other.lo_open) }
+            else { (self.lo, self.lo_open || other.lo_open) };
+        let (hi, hi_open) = if self.hi < other.hi { (self.hi, self.hi_open) }
+            else if other.hi < self.hi { (other.hi, other.hi_open) }
+            else { (self.hi, self.hi_open || other.hi_open) };
+        if lo > hi || (lo == hi && (lo_open || hi_open)) {
+            return None;
+        }
+        Some(Interval { lo, hi, lo_open, hi_open })
+    }
+
+    /// Single interval covering both, None when a gap separates them
+    /// (including a shared endpoint that neither contains)
+    pub fn union(&self, other: &Interval) -> Option<Interval> {
+        let (first, second) = if self.lo < other.lo || (self.lo == other.lo && !self.lo_open) { (self, other) } else { (other, self) };
+        if second.lo > first.hi || (second.lo == first.hi && first.hi_open && second.lo_open) {
+            return None;
+        }
+        let (hi, hi_open) = if first.hi > second.hi { (first.hi, first.hi_open) }
+            else if second.hi > first.hi { (second.hi, second.hi_open) }
+            else { (first.hi, first.hi_open && second.hi_open) };
+        Some(Interval { lo: first.lo, hi, lo_open: first.lo_open, hi_open })
+    }
+
+    /// Format in the requested notation
+    pub fn fmt_styled(&self, style: DisplayStyle) -> String {
+        self.fmt_with(style, &DefaultFormatter)
+    }
+
+    /// Format in the requested notation, with bounds written by `symbols`
+    pub fn fmt_with(&self, style: DisplayStyle, symbols: &dyn SymbolFormatter) -> String {
+        // A single attained point, like the 0 in floor(x)/x's range
+        if self.lo == self.hi && !self.lo_open && !self.hi_open {
+            return match style {
+                DisplayStyle::SymPy => format!("FiniteSet({})", symbols.format(self.lo)),
+                DisplayStyle::Brackets => format!("{{{}}}", symbols.format(self.lo)),
+            };
+        }
+        match style {
+            DisplayStyle::SymPy => {
+                let suffix = match (self.lo_open, self.hi_open) {
+                    (true, true) => ".open",
+                    (true, false) => ".Lopen",
+                    (false, true) => ".Ropen",
+                    (false, false) => "",
+                };
+                format!("Interval{}({}, {})", suffix, symbols.format(self.lo), symbols.format(self.hi))
+            }
+            DisplayStyle::Brackets => {
+                let left = if self.lo_open { "(" } else { "[" };
+                let right = if self.hi_open { ")" } else { "]" };
+                format!("{}{}, {}{}", left, symbols.format(self.lo), symbols.format(self.hi), right)
+            }
+        }
+    }
+}
+
+/// Union of any number of intervals as disjoint pieces in increasing order
+fn merge_intervals(mut parts: Vec<Interval>) -> Vec<Interval> {
+    parts.sort_by(|a, b| a.lo.partial_cmp(&b.lo).unwrap().then(a.lo_open.cmp(&b.lo_open)));
+    let mut merged: Vec<Interval> = Vec::new();
+    for part in parts {
+        match merged.last().and_then(|last| last.union(&part)) {
+            Some(joined) => *merged.last_mut().unwrap() = joined,
+            None => merged.push(part),
+        }
+    }
+    merged
+}
+
+impl std::fmt::Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.fmt_styled(DisplayStyle::SymPy))
+    }
+}
+
+// =============================================================================
+// DOMAIN REPRESENTATION
+// =============================================================================
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Domain {
+    Reals,
+    Interval(Interval),
+    /// Union of disjoint intervals (for rational functions with singularities)
+    UnionOfIntervals(Vec<Interval>),
+    Complement { base: Box<Domain>, excluded: Vec<f64> },
+    /// For periodic exclusions like tan(x) excluding pi/2 + n*pi
+    PeriodicComplement { pattern: String },
+    /// Whole numbers only, e.g. (-1)^x which is real only at integer x
+    IntegerSet,
+    Empty,
+}
+
+impl Domain {
+    /// Empty, one interval or a union, by how many parts there are
+    fn from_parts(parts: Vec<Interval>) -> Domain {
+        match parts.len() {
+            0 => Domain::Empty,
+            1 => Domain::Interval(parts[0]),
+            _ => Domain::UnionOfIntervals(parts),
+        }
+    }
+
+    /// The domain as disjoint intervals, None for periodic and integer domains
+    fn as_intervals(&self) -> Option<Vec<Interval>> {
+        match self {
+            Domain::Reals => Some(vec![Interval::reals()]),
+            Domain::Interval(part) => Some(vec![*part]),
+            Domain::UnionOfIntervals(parts) => Some(parts.clone()),
+            Domain::Complement { base, excluded } => {
+                let mut cuts = excluded.clone();
+                cuts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                Some(base.as_intervals()?.into_iter()
+                    .flat_map(|part| {
+                        let mut pieces = Vec::new();
+                        let (mut lo, mut lo_open) = (part.lo, part.lo_open);
+                        for &c in cuts.iter().filter(|&&c| part.contains(c)) {
+                            pieces.extend(Interval::new(lo, c, lo_open, true).intersect(&part));
+                            (lo, lo_open) = (c, true);
+                        }
+                        pieces.extend(Interval::new(lo, part.hi, lo_open, part.hi_open).intersect(&part));
+                        pieces
+                    })
+                    .collect())
+            }
+            Domain::Empty => Some(Vec::new()),
+            Domain::PeriodicComplement { .. } | Domain::IntegerSet => None,
+        }
+    }
+
+    /// Membership test; periodic exclusions aren't enumerated, so they count as inside
+    fn contains(&self, x: f64) -> bool {
+        match self {
+            Domain::Reals | Domain::PeriodicComplement { .. } => true,
+            Domain::Interval(part) => part.contains(x),
+            Domain::UnionOfIntervals(parts) => parts.iter().any(|part| part.contains(x)),
+            Domain::Complement { base, excluded } => base.contains(x) && !excluded.contains(&x),
+            Domain::IntegerSet => x.is_finite() && x.fract() == 0.0,
+            Domain::Empty => false,
+        }
+    }
+
+    /// Smallest and largest points of the domain
+    fn hull(&self) -> (f64, f64) {
+        match self {
+            Domain::Interval(part) => (part.lo, part.hi),
+            Domain::UnionOfIntervals(parts) => match (parts.first(), parts.last()) {
+                (Some(first), Some(last)) => (first.lo, last.hi),
+                _ => (f64::NAN, f64::NAN),
+            },
+            Domain::Complement { base, .. } => base.hull(),
+            Domain::Empty => (f64::NAN, f64::NAN),
+            Domain::Reals | Domain::PeriodicComplement { .. } | Domain::IntegerSet => {
+                (f64::NEG_INFINITY, f64::INFINITY)
+            }
+        }
+    }
+
+    /// Intersection with an interval. Excluded points inside the window stay
+    /// excluded, periodic ones enumerated from their lattice; integer domains are kept whole.
+    fn restrict(&self, window: &Interval) -> Domain {
+        let to_domain = Domain::from_parts;
+        match self {
+            Domain::Reals => to_domain(vec![*window]),
+            Domain::PeriodicComplement { pattern } => {
+                let poles = lattice_points(pattern, window.lo, window.hi).unwrap_or_default();
+                exclude_points(to_domain(vec![*window]), &poles)
+            }
+            Domain::Complement { base, excluded } => exclude_points(base.restrict(window), excluded),
+            Domain::Interval(part) => to_domain(part.intersect(window).into_iter().collect()),
+            Domain::UnionOfIntervals(parts) => to_domain(parts.iter().filter_map(|part| part.intersect(window)).collect()),
+            Domain::IntegerSet => Domain::IntegerSet,
+            Domain::Empty => Domain::Empty,
+        }
+    }
+}
+
+/// Notation for printing intervals
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DisplayStyle {
+    /// SymPy-style constructors, e.g. `Interval.Ropen(0, oo)`
+    SymPy,
+    /// Math bracket notation, e.g. `[0, oo)`
+    Brackets,
+}
+
+impl Domain {
+    pub fn fmt_styled(&self, style: DisplayStyle) -> String {
+        self.fmt_with(style, &DefaultFormatter)
+    }
+
+    /// Format in the requested notation, with points written by `symbols`
+    pub fn fmt_with(&self, style: DisplayStyle, symbols: &dyn SymbolFormatter) -> String {
+        match (self, style) {
+            (Domain::Reals, _) => "Reals".to_string(),
+            (Domain::Interval(part), _) => part.fmt_with(style, symbols),
+            (Domain::UnionOfIntervals(intervals), _) => {
+                let parts: Vec<String> = intervals.iter().map(|part| part.fmt_with(style, symbols)).collect();
+                match style {
+                    DisplayStyle::SymPy => format!("Union({})", parts.join(", ")),
+                    DisplayStyle::Brackets => parts.join(" U "),
+                }
+            }
+            (Domain::Complement { excluded, .. }, _) => {
+                let excl: Vec<String> = excluded.iter().map(|x| symbols.format(*x)).collect();
+                match style {
+                    DisplayStyle::SymPy => format!("Complement(Reals, {{{}}})", excl.join(", ")),
+                    DisplayStyle::Brackets => format!("Reals \\ {{{}}}", excl.join(", ")),
+                }
+            }
+            (Domain::PeriodicComplement { pattern }, _) => format!("Complement(Reals, {})", pattern),
+            (Domain::IntegerSet, _) => "Integers".to_string(),
+            (Domain::Empty, DisplayStyle::SymPy) => "EmptySet".to_string(),
+            (Domain::Empty, DisplayStyle::Brackets) => "Empty".to_string(),
+        }
+    }
+
+    /// Readable bracket notation, e.g. `[0, oo)`
+    pub fn fmt_brackets(&self) -> String {
+        self.fmt_styled(DisplayStyle::Brackets)
+    }
+}
+
+impl std::fmt::Display for Domain {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.fmt_styled(DisplayStyle::SymPy))
+    }
+}
+
+// =============================================================================
+// RANGE REPRESENTATION
+// =============================================================================
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RangeType {
+    Simple,
+    /// Split range like 1/x: (-oo, 0) U (0, oo)
+    SplitAtValue { excluded: f64 },
+    /// Cosecant/Secant type: (-oo, -a] U [a, oo)
+    UnionExterior { bound: f64, closed: bool },
+    /// Integer set (for floor/ceiling)
+    Integers,
+    /// Custom union of intervals
+    CustomUnion { parts: Vec<Interval> },
+    /// Finitely many values, e.g. {0, 1/2, 1} for heaviside(x)
+    FiniteSet(Vec<f64>),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Range {
+    /// Smallest interval holding every value; the whole range when `Simple`
+    pub hull: Interval,
+    pub range_type: RangeType,
+}
+
+impl Range {
+    /// The values as disjoint intervals, points for a finite set. None for Integers.
+    fn as_intervals(&self) -> Option<Vec<Interval>> {
+        let (lo, hi) = (f64::NEG_INFINITY, f64::INFINITY);
+        match &self.range_type {
+            RangeType::Simple => Some(vec![self.hull]),
+            RangeType::SplitAtValue { excluded } => Some(vec![Interval::new(lo, *excluded, true, true), Interval::new(*excluded, hi, true, true)]),
+            RangeType::UnionExterior { bound, closed } => Some(vec![Interval::new(lo, -bound, true, !closed), Interval::new(*bound, hi, !closed, true)]),
+            RangeType::CustomUnion { parts } => Some(parts.clone()),
+            RangeType::FiniteSet(values) => Some(values.iter().map(|&v| Interval::new(v, v, false, false)).collect()),
+            RangeType::Integers => None,
+        }
+    }
+}
+
+impl Range {
+    /// SymPy-style notation with bounds written by `symbols`
+    pub fn fmt_with(&self, symbols: &dyn SymbolFormatter) -> String {
+        match &self.range_type {
+            RangeType::SplitAtValue { excluded } => {
+                let e = symbols.format(*excluded);
+                format!("Union(Interval.open(-oo, {}), Interval.open({}, oo))", e, e)
+            }
+            RangeType::UnionExterior { bound, closed } => {
+                let b = symbols.format(*bound);
+                if *closed {
+                    format!("Union(Interval(-oo, -{}], Interval[{}, oo))", b, b)
+                } else {
+                    format!("Union(Interval.open(-oo, -{}), Interval.open({}, oo))", b, b)
+                }
+            }
+            RangeType::Integers => "Integers".to_string(),
+            RangeType::FiniteSet(values) => {
+                let strs: Vec<String> = values.iter().map(|v| symbols.format(*v)).collect();
+                format!("FiniteSet({})", strs.join(", "))
+            }
+            RangeType::CustomUnion { parts } => {
+                let strs: Vec<String> = parts.iter().map(|part| part.fmt_with(DisplayStyle::SymPy, symbols)).collect();
+                format!("Union({})", strs.join(", "))
+            }
+            RangeType::Simple if self.hull.lo == f64::NEG_INFINITY && self.hull.hi == f64::INFINITY => "Reals".to_string(),
+            RangeType::Simple => self.hull.fmt_with(DisplayStyle::SymPy, symbols),
+        }
+    }
+}
+
+impl std::fmt::Display for Range {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.fmt_with(&DefaultFormatter))
+    }
+}
+
+// =============================================================================
+// RESULT STRUCTURE
+// =============================================================================
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SolveResult {
+    pub domain: Domain,
+    pub range: Range,
+    pub method: String,
+    /// Points where a heuristic fell back or its accuracy is doubtful
+    pub warnings: Vec<String>,
+}
+
+/// Color-free block with one `Label: value` line per field, labels padded to
+/// equal width. Each warning adds a `Warn:   ` line.
+impl std::fmt::Display for SolveResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.fmt_with(&DefaultFormatter))
+    }
+}
+
+impl SolveResult {
+    /// The `Display` block with domain and range written by `symbols`
+    pub fn fmt_with(&self, symbols: &dyn SymbolFormatter) -> String {
+        let mut out = format!("Domain: {}\nRange:  {}\nMethod: {}",
+            self.domain.fmt_with(DisplayStyle::SymPy, symbols), self.range.fmt_with(symbols), self.method);
+        for warning in &self.warnings {
+            out.push_str(&format!("\nWarn:   {}", warning));
+        }
+        out
+    }
+
+    /// Compact binary encoding (bincode), e.g. for caching results on disk
+    pub fn to_bytes(&self) -> bincode::Result<Vec<u8>> {
+        bincode::serialize(self)
+    }
+
+    /// Inverse of `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> bincode::Result<SolveResult> {
+        bincode::deserialize(bytes)
+    }
+
+    /// One result for a function pieced together from several, e.g. the
+    /// per-interval results of a piecewise definition: domains and ranges are
+    /// unioned, and the method names the pieces' methods. A periodic or integer
+    /// domain, or an integer range, mixed with other shapes widens that part to
+    /// the hull of all pieces.
+    pub fn combine(results: &[SolveResult]) -> SolveResult {
+        let Some(first) = results.first() else {
+            return SolveResult {
+                domain: Domain::Empty,
+                range: Range { hull: Interval::new(0.0, 0.0, true, true), range_type: RangeType::Simple },
+                method: "Undefined".to_string(),
+                warnings: vec!["no pieces to combine".to_string()],
+            };
+        };
+        let mut warnings: Vec<String> = Vec::new();
+        
+        let domain = if results.iter().all(|r| r.domain == first.domain) {
+            first.domain.clone()
+        } else if let Some(parts) = results.iter().map(|r| r.domain.as_intervals()).collect::<Option<Vec<_>>>() {
+            match merge_intervals(parts.concat()).as_slice() {
+                [only] if *only == Interval::reals() => Domain::Reals,
+                merged => Domain::from_parts(merged.to_vec()),
+            }
+        } else {
+            let (lo, hi) = results.iter().map(|r| r.domain.hull())
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), (a, b)| (lo.min(a), hi.max(b)));
+            warnings.push("pieces' domains have no common interval form; domain shown as their hull".to_string());
+            Domain::Interval(Interval::new(lo, hi, lo.is_infinite(), hi.is_infinite()))
+        };
+        
+        let hull = results.iter().map(|r| r.range.hull).reduce(|a, b| {
+            let (lo, lo_open) = if a.lo < b.lo { (a.lo, a.lo_open) } else if b.lo < a.lo { (b.lo, b.lo_open) } else { (a.lo, a.lo_open && b.lo_open) };
+            let (hi, hi_open) = if a.hi > b.hi { (a.hi, a.hi_open) } else if b.hi > a.hi { (b.hi, b.hi_open) } else { (a.hi, a.hi_open && b.hi_open) };
+            Interval::new(lo, hi, lo_open, hi_open)
+        }).unwrap_or(first.range.hull);
+        let range_type = if results.iter().all(|r| r.range.range_type == RangeType::Integers) {
+            RangeType::Integers
+        } else if let Some(parts) = results.iter().map(|r| r.range.as_intervals()).collect::<Option<Vec<_>>>() {
+            let merged = merge_intervals(parts.concat());
+            if merged.iter().all(|part| part.lo == part.hi) {
+                RangeType::FiniteSet(merged.iter().map(|part| part.lo).collect())
+            } else if merged.len() == 1 {
+                RangeType::Simple
+            } else {
+                RangeType::CustomUnion { parts: merged }
+            }
+        } else {
+            warnings.push("integer-valued pieces mixed with others; range shown as the hull".to_string());
+            RangeType::Simple
+        };
+        
+        let mut methods: Vec<&str> = Vec::new();
+        for r in results {
+            if !methods.contains(&r.method.as_str()) {
+                methods.push(&r.method);
+            }
+            warnings.extend(r.warnings.iter().filter(|w| !warnings.contains(w)).cloned().collect::<Vec<_>>());
+        }
+        let method = match methods.as_slice() {
+            [only] => only.to_string(),
+            _ => format!("Combined ({})", methods.join(", ")),
+        };
+        
+        SolveResult { domain, range: Range { hull, range_type }, method, warnings }
+    }
+}
+
+/// The `Display` block as a string, for frontends that can't render ANSI codes
+pub fn format_plain(result: &SolveResult) -> String {
+    result.to_string()
+}
+
+/// A few plain English sentences on why the domain and range came out as they
+/// did, assembled from the features the solver looks for: poles, radicands and
+/// log arguments, symmetry, attained or approached bounds, skipped values.
+/// `func_str` is the expression `result` was solved for.
+pub fn explain(result: &SolveResult, func_str: &str) -> String {
+    let expr_str = canonical_variable(func_str).map(|s| rewrite_odd_roots(&s)).unwrap_or_default();
+    let lower = expr_str.to_lowercase();
+    let func = expr_str.parse::<Expr>().ok().and_then(|e| bind_x(e).ok());
+    let listed = |xs: &[f64]| xs.iter().map(|&x| format_symbolic(x)).collect::<Vec<_>>().join(", ");
+    let mut sentences = Vec::new();
+    
+    // Domain: the poles first, then whatever else cuts the real line. A blow-up
+    // at a domain end (ln(x) at 0) is down to the end, not a division.
+    let poles: Vec<f64> = find_singularities(func_str).into_iter()
+        .filter(|&p| result.domain.contains(p - 1e-6) && result.domain.contains(p + 1e-6))
+        .collect();
+    // Points where the function stays bounded, like 0 for sin(x)/x, are holes
+    let (holes, poles): (Vec<f64>, Vec<f64>) = poles.iter().partition(|&&p| {
+        func.as_ref().is_some_and(|f| [-1.0, 1.0].iter().all(|side| safe_eval(f, p + side * 1e-7).is_some_and(|v| v.abs() < 1e6)))
+    });
+    if !poles.is_empty() && !matches!(result.domain, Domain::PeriodicComplement { .. }) {
+        let cause = if lower.contains('/') || lower.contains("^(-") { " because of division by zero there" } else { ", where the function blows up" };
+        sentences.push(format!("Domain excludes the pole{} x = {}{}.", if poles.len() > 1 { "s" } else { "" }, listed(&poles), cause));
+    }
+    if !holes.is_empty() {
+        sentences.push(format!("Domain excludes x = {}, where the expression is 0/0 though the function stays bounded nearby.", listed(&holes)));
+    }
+    let mut limits = Vec::new();
+    if lower.contains("sqrt") || lower.contains("^(1/2)") { limits.push("a square root needs a non-negative argument"); }
+    if lower.contains("ln(") || lower.contains("log(") { limits.push("a logarithm needs a positive argument"); }
+    if lower.contains("asin(") || lower.contains("acos(") { limits.push("asin and acos need an argument in [-1, 1]"); }
+    match &result.domain {
+        Domain::Reals => sentences.push("The function is defined for every real x.".to_string()),
+        Domain::Empty => sentences.push("The function is defined for no real x.".to_string()),
+        Domain::IntegerSet => sentences.push("The function is real only at integer x, where a negative base meets a whole exponent.".to_string()),
+        Domain::PeriodicComplement { pattern } => {
+            let kind = if pattern.ends_with("Integers)") { "periodic points" } else { "evenly spaced points" };
+            sentences.push(format!("Domain excludes the {} {} where the function has poles.", kind, pattern));
+        }
+        domain if !limits.is_empty() => sentences.push(format!("Domain is {} because {}.", domain, limits.join(" and "))),
+        Domain::Complement { .. } | Domain::UnionOfIntervals(_) if !poles.is_empty() || !holes.is_empty() => {}
+        domain => sentences.push(format!("Domain is {}.", domain)),
+    }
+    
+    // Symmetry, judged at a few points where both sides are defined
+    if let Some(func) = &func {
+        let pairs: Vec<(f64, f64)> = [0.3, 0.7, 1.3, 2.9].iter()
+            .filter_map(|&x| Some((safe_eval(func, x)?, safe_eval(func, -x)?)))
+            .collect();
+        let close = |a: f64, b: f64| (a - b).abs() <= 1e-9 * a.abs().max(1.0);
+        if pairs.len() >= 2 && pairs.iter().all(|&(a, b)| close(a, b)) && pairs.iter().any(|&(a, _)| a != pairs[0].0) {
+            sentences.push("The function is even, f(-x) = f(x), so its values for x >= 0 are all of them.".to_string());
+        } else if pairs.len() >= 2 && pairs.iter().all(|&(a, b)| close(a, -b)) {
+            sentences.push("The function is odd, f(-x) = -f(x), so its range is symmetric about 0.".to_string());
+        }
+    }
+    
+    // Range: how each end is reached, then anything skipped in between
+    let range = &result.range;
+    match &range.range_type {
+        RangeType::Integers => sentences.push("Range is the integers: the function only takes whole-number values.".to_string()),
+        RangeType::FiniteSet(values) => sentences.push(format!("The function only takes the values {}.", listed(values))),
+        range_type => {
+            let Interval { lo, hi, lo_open, hi_open } = range.hull;
+            let end = |value: f64, open: bool, word: &str, side: &str| match (value.is_finite(), open) {
+                (false, _) if !poles.is_empty() => format!("it is unbounded {} near the poles or far out", side),
+                (false, _) => format!("it is unbounded {}", side),
+                (true, false) => format!("the {} {} is attained", word, format_symbolic(value)),
+                (true, true) => format!("{} is approached but never reached", format_symbolic(value)),
+            };
+            let ends = if lo == f64::NEG_INFINITY && hi == f64::INFINITY {
+                "it is unbounded in both directions".to_string()
+            } else {
+                format!("{}, and {}", end(lo, lo_open, "minimum", "below"), end(hi, hi_open, "maximum", "above"))
+            };
+            sentences.push(format!("Range is {}: {}.", range, ends));
+            match range_type {
+                RangeType::SplitAtValue { excluded } => {
+                    sentences.push(format!("The value {} is never taken; it is only approached.", format_symbolic(*excluded)));
+                }
+                RangeType::UnionExterior { bound, .. } => {
+                    sentences.push(format!("Values strictly between -{b} and {b} are skipped.", b = format_symbolic(*bound)));
+                }
+                RangeType::CustomUnion { parts } => {
+                    for w in parts.windows(2) {
+                        if w[0].hi == w[1].lo {
+                            sentences.push(format!("The value {} is never taken.", format_symbolic(w[0].hi)));
+                        } else {
+                            sentences.push(format!("Values between {} and {} are skipped.", format_symbolic(w[0].hi), format_symbolic(w[1].lo)));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    
+    for warning in &result.warnings {
+        sentences.push(format!("Caveat: {}.", warning));
+    }
+    sentences.join(" ")
+}
+
+/// Reasons an expression can't be analyzed at all
+#[derive(Debug, Clone)]
+pub enum SolveError {
+    /// meval could not parse the expression
+    Parse(String),
+    /// The expression could not be bound to the variable `x`
+    Bind(String),
+}
+
+impl std::fmt::Display for SolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SolveError::Parse(msg) => write!(f, "Parse error: {}", msg),
+            SolveError::Bind(msg) => write!(f, "Bind error: {}", msg),
+        }
+    }
+}
+
+// =============================================================================
+// UTILITY FUNCTIONS
+// =============================================================================
+
+/// `f` over every item, in order. Rayon gives each worker thread its own
+/// `init()` state (a bound expression, say); without the `rayon` feature one
+/// state serves the whole run on the calling thread.
+#[cfg(feature = "rayon")]
+fn map_init<T, S, R>(items: &[T], init: impl Fn() -> S + Sync + Send, f: impl Fn(&mut S, &T) -> R + Sync + Send) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+{
+    items.par_iter().map_init(init, f).collect()
+}
+
+#[cfg(not(feature = "rayon"))]
+fn map_init<T, S, R>(items: &[T], init: impl Fn() -> S, f: impl Fn(&mut S, &T) -> R) -> Vec<R> {
+    let mut state = init();
+    items.iter().map(|item| f(&mut state, item)).collect()
+}
+
+fn is_valid(val: f64) -> bool {
+    val.is_finite() && !val.is_nan()
+}
+
+fn safe_eval(func: &impl Fn(f64) -> f64, x: f64) -> Option<f64> {
+    let val = func(x);
+    if is_valid(val) { Some(val) } else { None }
+}
+
+/// meval's builtins plus the functions it lacks
+fn solver_context() -> Context<'static> {
+    let mut ctx = Context::new();
+    // Half-maximum convention: H(0) = 1/2
+    ctx.func("heaviside", |x| match x.partial_cmp(&0.0) {
+        Some(std::cmp::Ordering::Greater) => 1.0,
+        Some(std::cmp::Ordering::Less) => 0.0,
+        Some(std::cmp::Ordering::Equal) => 0.5,
+        None => f64::NAN,
+    });
+    ctx.func("gamma", gamma);
+    ctx.func("csc", |x| 1.0 / x.sin());
+    ctx.func("sec", |x| 1.0 / x.cos());
+    ctx.func("cot", |x| 1.0 / x.tan());
+    ctx.func("sech", |x| 1.0 / x.cosh());
+    ctx.func("csch", |x| 1.0 / x.sinh());
+    // Real n-th root: odd roots of negative numbers are negative
+    ctx.func2("root", |x, n| {
+        if x < 0.0 && n.fract() == 0.0 && n % 2.0 != 0.0 { -(-x).powf(1.0 / n) } else { x.powf(1.0 / n) }
+    });
+    ctx
+}
+
+/// Gamma function: Lanczos approximation (g = 7, 9 terms), reflected below 1/2.
+/// NaN at the poles 0, -1, -2, ...
+fn gamma(x: f64) -> f64 {
+    const G: f64 = 7.0;
+    const COEFFS: [f64; 9] = [
+        0.999_999_999_999_809_9, 676.520_368_121_885_1, -1_259.139_216_722_402_8,
+        771.323_428_777_653_1, -176.615_029_162_140_6, 12.507_343_278_686_905,
+        -0.138_571_095_265_720_12, 9.984_369_578_019_572e-6, 1.505_632_735_149_311_6e-7,
+    ];
+    if x <= 0.0 && x.fract() == 0.0 {
+        return f64::NAN;
+    }
+    if x > 171.7 {
+        return f64::INFINITY;   // beyond f64::MAX
+    }
+    if x < 0.5 {
+        return PI / ((PI * x).sin() * gamma(1.0 - x));
+    }
+    let x = x - 1.0;
+    let t = x + G + 0.5;
+    let sum = COEFFS[1..].iter().enumerate()
+        .fold(COEFFS[0], |acc, (i, &c)| acc + c / (x + i as f64 + 1.0));
+    // t^(x + 1/2) split in two so it doesn't overflow before exp(-t) scales it down
+    let half_power = t.powf(0.5 * (x + 0.5));
+    (2.0 * PI).sqrt() * half_power * (-t).exp() * half_power * sum
+}
+
+/// Rewrite the expression's one free variable (`t`, `theta`, ...) as `x`, the
+/// name every stage assumes. None when it has several variables or doesn't parse.
+fn canonical_variable(func_str: &str) -> Option<String> {
+    let expr: Expr = func_str.parse().ok()?;
+    let mut names: Vec<&str> = expr.iter()
+        .filter_map(|token| match token {
+            Token::Var(name) if name != "pi" && name != "e" => Some(name.as_str()),
+            _ => None,
+        })
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+    match names.as_slice() {
+        [] | ["x"] => Some(func_str.to_string()),
+        [name] => {
+            let re = Regex::new(&format!(r"\b{}\b", regex::escape(name))).ok()?;
+            Some(re.replace_all(func_str, "x").into_owned())
+        }
+        _ => None,
+    }
+}
+
+/// Bind `x` against the solver's context
+fn bind_x(expr: Expr) -> Result<impl Fn(f64) -> f64, meval::Error> {
+    expr.bind_with_context(solver_context(), "x")
+}
+
+// =============================================================================
+// EXPRESSION TREE - Structural view of the parsed expression
+// =============================================================================
+
+/// Expression tree rebuilt from meval's RPN tokens, used for structural pattern detection
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Num(f64),
+    Var(String),
+    Neg(Box<Node>),
+    Add(Box<Node>, Box<Node>),
+    Sub(Box<Node>, Box<Node>),
+    Mul(Box<Node>, Box<Node>),
+    Div(Box<Node>, Box<Node>),
+    Pow(Box<Node>, Box<Node>),
+    Func(String, Vec<Node>),
+}
+
+/// Rebuild a tree from the RPN token stream of a parsed expression
+fn build_tree(expr: &Expr) -> Option<Node> {
+    let mut stack: Vec<Node> = Vec::new();
+    for token in expr.iter() {
+        match token {
+            Token::Number(n) => stack.push(Node::Num(*n)),
+            Token::Var(name) => stack.push(match name.as_str() {
+                "pi" => Node::Num(PI),
+                "e" => Node::Num(E),
+                _ => Node::Var(name.clone()),
+            }),
+            Token::Unary(Operation::Plus) => {}
+            Token::Unary(Operation::Minus) => {
+                let a = stack.pop()?;
+                stack.push(Node::Neg(Box::new(a)));
+            }
+            Token::Binary(op) => {
+                let b = Box::new(stack.pop()?);
+                let a = Box::new(stack.pop()?);
+                stack.push(match op {
+                    Operation::Plus => Node::Add(a, b),
+                    Operation::Minus => Node::Sub(a, b),
+                    Operation::Times => Node::Mul(a, b),
+                    Operation::Div => Node::Div(a, b),
+                    Operation::Pow => Node::Pow(a, b),
+                    _ => return None,
+                });
+            }
+            Token::Func(name, Some(n)) => {
+                let args = stack.split_off(stack.len().checked_sub(*n)?);
+                stack.push(Node::Func(name.clone(), args));
+            }
+            _ => return None,
+        }
+    }
+    if stack.len() == 1 { stack.pop() } else { None }
+}
+
+/// Polynomial coefficients (lowest degree first) if the node is a polynomial in x
+fn as_polynomial(node: &Node) -> Option<Vec<f64>> {
+    let poly = match node {
+        Node::Num(c) => vec![*c],
+        Node::Var(_) => vec![0.0, 1.0],
+        Node::Neg(a) => as_polynomial(a)?.iter().map(|c| -c).collect(),
+        Node::Add(l, r) => poly_add(&as_polynomial(l)?, &as_polynomial(r)?, 1.0),
+        Node::Sub(l, r) => poly_add(&as_polynomial(l)?, &as_polynomial(r)?, -1.0),
+        Node::Mul(l, r) => poly_mul(&as_polynomial(l)?, &as_polynomial(r)?),
+        Node::Div(l, r) => {
+            let divisor = as_polynomial(r)?;
+            if divisor.len() != 1 || divisor[0] == 0.0 {
+                return None;
+            }
+            as_polynomial(l)?.iter().map(|c| c / divisor[0]).collect()
+        }
+        Node::Pow(base, exponent) => {
+            let n = match exponent.as_ref() { Node::Num(n) => *n, _ => return None };
+            if n < 0.0 || n.fract() != 0.0 || n > 32.0 {
+                return None;
+            }
+            let base = as_polynomial(base)?;
+            (0..n as usize).fold(vec![1.0], |acc, _| poly_mul(&acc, &base))
+        }
+        // A constant call such as sqrt(2) is just its value
+        Node::Func(..) if count_vars(node) == 0 => vec![meval::eval_str(canonical_node(node)).ok().filter(|c: &f64| c.is_finite())?],
+        Node::Func(..) => return None,
+    };
+    Some(poly_trim(poly))
+}
+
+fn poly_add(a: &[f64], b: &[f64], sign: f64) -> Vec<f64> {
+    (0..a.len().max(b.len()))
+        .map(|i| a.get(i).copied().unwrap_or(0.0) + sign * b.get(i).copied().unwrap_or(0.0))
+        .collect()
+}
+
+fn poly_mul(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut out = vec![0.0; a.len() + b.len() - 1];
+    for (i, ca) in a.iter().enumerate() {
+        for (j, cb) in b.iter().enumerate() {
+            out[i + j] += ca * cb;
+        }
+    }
+    out
+}
+
+/// Drop vanishing leading coefficients so `len() - 1` is the degree
+fn poly_trim(mut poly: Vec<f64>) -> Vec<f64> {
+    while poly.len() > 1 && poly[poly.len() - 1] == 0.0 {
+        poly.pop();
+    }
+    poly
+}
+
+/// Coefficients `(a, b)` if the node is the linear form `a*x + b`
+fn as_linear(node: &Node) -> Option<(f64, f64)> {
+    let poly = as_polynomial(node)?;
+    match poly.len() {
+        1 => Some((0.0, poly[0])),
+        2 => Some((poly[1], poly[0])),
+        _ => None,
+    }
+}
+
+// =============================================================================
+// CANONICAL FORM - One spelling per expression, for cache keys
+// =============================================================================
+
+/// Binding strength of a node's top operator; atoms bind tightest
+fn precedence(node: &Node) -> u8 {
+    match node {
+        Node::Add(..) | Node::Sub(..) => 1,
+        Node::Mul(..) | Node::Div(..) => 2,
+        Node::Neg(_) => 3,
+        Node::Num(c) if *c < 0.0 => 3,
+        Node::Pow(..) => 4,
+        Node::Num(_) | Node::Var(_) | Node::Func(..) => 5,
+    }
+}
+
+/// Operands of a run of `+` (or of `*`), however the run was nested
+fn sum_terms(node: &Node) -> Vec<&Node> {
+    match node {
+        Node::Add(l, r) => sum_terms(l).into_iter().chain(sum_terms(r)).collect(),
+        _ => vec![node],
+    }
+}
+
+fn product_factors(node: &Node) -> Vec<&Node> {
+    match node {
+        Node::Mul(l, r) => product_factors(l).into_iter().chain(product_factors(r)).collect(),
+        _ => vec![node],
+    }
+}
+
+/// Serialize with the operands of `+` and `*` sorted, parenthesizing a child
+/// only where its operator binds looser than the slot it sits in
+fn canonical_node(node: &Node) -> String {
+    let wrap = |child: &Node, min: u8| {
+        let text = canonical_node(child);
+        if precedence(child) < min { format!("({})", text) } else { text }
+    };
+    let sorted = |mut parts: Vec<String>, sep: &str| {
+        parts.sort();
+        parts.join(sep)
+    };
+    match node {
+        Node::Num(c) => format!("{}", c),
+        Node::Var(name) => name.clone(),
+        Node::Neg(a) => format!("-{}", wrap(a, 4)),
+        Node::Add(..) => sorted(sum_terms(node).into_iter().map(|t| wrap(t, 1)).collect(), "+"),
+        Node::Mul(..) => sorted(product_factors(node).into_iter().map(|f| wrap(f, 2)).collect(), "*"),
+        Node::Sub(l, r) => format!("{}-{}", wrap(l, 1), wrap(r, 2)),
+        Node::Div(l, r) => format!("{}/{}", wrap(l, 2), wrap(r, 3)),
+        Node::Pow(base, exponent) => format!("{}^{}", wrap(base, 5), wrap(exponent, 4)),
+        Node::Func(name, args) => format!("{}({})", name, args.iter().map(canonical_node).collect::<Vec<_>>().join(", ")),
+    }
+}
+
+/// Order-independent spelling of an expression: the variable renamed to x and
+/// the operands of `+` and `*` sorted, so `x^2 + 1` and `1 + t^2` share a key.
+/// None if the expression doesn't parse.
+pub fn canonicalize(func_str: &str) -> Option<String> {
+    let expr: Expr = canonical_variable(func_str)?.parse().ok()?;
+    build_tree(&expr).map(|tree| canonical_node(&tree))
+}
+
+// =============================================================================
+// STEP FUNCTIONS - Finitely many values
+// =============================================================================
+
+/// Functions that jump between finitely many levels
+const STEP_FUNCTIONS: &[&str] = &["heaviside", "signum"];
+
+/// True when x only enters through step functions, so only finitely many values occur
+fn is_step_valued(node: &Node) -> bool {
+    match node {
+        Node::Num(_) => true,
+        Node::Var(_) => false,
+        Node::Neg(a) => is_step_valued(a),
+        Node::Add(l, r) | Node::Sub(l, r) | Node::Mul(l, r) | Node::Div(l, r) | Node::Pow(l, r) => {
+            is_step_valued(l) && is_step_valued(r)
+        }
+        Node::Func(name, args) => STEP_FUNCTIONS.contains(&name.as_str()) || args.iter().all(is_step_valued),
+    }
+}
+
+/// True when every value is an integer: floor/ceil results combined by +, -
+/// and * with integer constants
+fn is_integer_valued(node: &Node) -> bool {
+    match node {
+        Node::Num(c) => c.fract() == 0.0,
+        Node::Var(_) => false,
+        Node::Neg(a) => is_integer_valued(a),
+        Node::Add(l, r) | Node::Sub(l, r) | Node::Mul(l, r) => is_integer_valued(l) && is_integer_valued(r),
+        Node::Div(..) | Node::Pow(..) => false,
+        Node::Func(name, _) => name == "floor" || name == "ceil",
+    }
+}
+
+/// Jump locations of step functions with a linear argument; the value exactly at
+/// a jump (e.g. heaviside(0) = 1/2) is a level of its own that sampling misses
+fn step_points(node: &Node) -> Vec<f64> {
+    match node {
+        Node::Num(_) | Node::Var(_) => Vec::new(),
+        Node::Neg(a) => step_points(a),
+        Node::Add(l, r) | Node::Sub(l, r) | Node::Mul(l, r) | Node::Div(l, r) | Node::Pow(l, r) => {
+            let mut points = step_points(l);
+            points.extend(step_points(r));
+            points
+        }
+        Node::Func(name, args) => {
+            let mut points: Vec<f64> = args.iter().flat_map(step_points).collect();
+            if STEP_FUNCTIONS.contains(&name.as_str()) && args.len() == 1 {
+                if let Some((a, b)) = as_linear(&args[0]).filter(|(a, _)| *a != 0.0) {
+                    points.push(-b / a);
+                }
+            }
+            points
+        }
+    }
+}
+
+// =============================================================================
+// PIECEWISE-LINEAR ANALYSIS - Sums of abs(x - a_i)
+// =============================================================================
+
+/// Breakpoints of a sum `c + sum w_i*abs(a_i*x + b_i)` with positive weights.
+/// Such a sum is convex and piecewise linear, so its minimum sits on a breakpoint.
+fn abs_sum_breakpoints(node: &Node) -> Option<Vec<f64>> {
+    fn collect(node: &Node, sign: f64, breakpoints: &mut Vec<f64>) -> Option<()> {
+        match node {
+            Node::Num(_) => Some(()),
+            Node::Add(l, r) => {
+                collect(l, sign, breakpoints)?;
+                collect(r, sign, breakpoints)
+            }
+            Node::Sub(l, r) => {
+                collect(l, sign, breakpoints)?;
+                collect(r, -sign, breakpoints)
+            }
+            Node::Neg(a) => collect(a, -sign, breakpoints),
+            Node::Mul(l, r) => match (l.as_ref(), r.as_ref()) {
+                (Node::Num(c), term) | (term, Node::Num(c)) => collect(term, sign * c, breakpoints),
+                _ => None,
+            },
+            Node::Func(name, args) if name == "abs" && args.len() == 1 => {
+                let (a, b) = as_linear(&args[0])?;
+                if sign <= 0.0 || a == 0.0 {
+                    return None;
+                }
+                breakpoints.push(-b / a);
+                Some(())
+            }
+            _ => None,
+        }
+    }
+
+    let mut breakpoints = Vec::new();
+    collect(node, 1.0, &mut breakpoints)?;
+    if breakpoints.is_empty() {
+        return None;
+    }
+    breakpoints.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    breakpoints.dedup();
+    Some(breakpoints)
+}
+
+// =============================================================================
+// COMPOSITION ANALYSIS - Exact ranges through affine and monotone maps
+// =============================================================================
+
+/// Value sets propagated through the expression tree are intervals too
+impl Interval {
+    fn point(c: f64) -> Interval {
+        Interval::new(c, c, false, false)
+    }
+
+    /// [lo, oo), or (lo, oo)
+    fn half_line(lo: f64, lo_open: bool) -> Interval {
+        Interval { lo, hi: f64::INFINITY, lo_open, hi_open: true }
+    }
+
+    fn is_point(&self) -> bool {
+        self.lo == self.hi
+    }
+
+    /// Image under an increasing map; f64 functions give the right limits at +/-inf
+    fn map_increasing(&self, f: impl Fn(f64) -> f64) -> Interval {
+        Interval { lo: f(self.lo), hi: f(self.hi), lo_open: self.lo_open, hi_open: self.hi_open }
+    }
+
+    fn map_decreasing(&self, f: impl Fn(f64) -> f64) -> Interval {
+        Interval { lo: f(self.hi), hi: f(self.lo), lo_open: self.hi_open, hi_open: self.lo_open }
+    }
+
+    /// Image under an even map that decreases on (-oo, 0] and increases on [0, oo)
+    fn map_even(&self, f: impl Fn(f64) -> f64) -> Interval {
+        if self.lo >= 0.0 {
+            return self.map_increasing(f);
+        }
+        if self.hi <= 0.0 {
+            return self.map_decreasing(f);
+        }
+        let (f_lo, f_hi) = (f(self.lo), f(self.hi));
+        let (hi, hi_open) = if f_lo > f_hi { (f_lo, self.lo_open) }
+            else if f_hi > f_lo { (f_hi, self.hi_open) }
+            else { (f_hi, self.lo_open && self.hi_open) };
+        Interval { lo: f(0.0), hi, lo_open: false, hi_open }
+    }
+
+    fn scale(&self, c: f64) -> Interval {
+        if c == 0.0 { Interval::point(0.0) }
+        else if c > 0.0 { self.map_increasing(|v| v * c) } else { self.map_decreasing(|v| v * c) }
+    }
+
+    fn add(&self, other: &Interval) -> Interval {
+        Interval {
+            lo: self.lo + other.lo,
+            hi: self.hi + other.hi,
+            lo_open: self.lo_open || other.lo_open,
+            hi_open: self.hi_open || other.hi_open,
+        }
+    }
+
+    /// 1/s for a span on one side of zero; a zero endpoint becomes an unattained infinity
+    fn reciprocal(&self) -> Option<Interval> {
+        if self.lo >= 0.0 {
+            let positive = self.intersect(&Interval { lo: 0.0, hi: f64::INFINITY, lo_open: true, hi_open: true })?;
+            Some(positive.map_decreasing(|v| 1.0 / v))
+        } else if self.hi <= 0.0 {
+            let negative = self.intersect(&Interval { lo: f64::NEG_INFINITY, hi: 0.0, lo_open: true, hi_open: true })?;
+            Some(negative.map_decreasing(|v| 1.0 / v))
+        } else {
+            None
+        }
+    }
+}
+
+/// Number of variable occurrences; interval propagation is exact only when this is 1
+fn count_vars(node: &Node) -> usize {
+    match node {
+        Node::Num(_) => 0,
+        Node::Var(_) => 1,
+        Node::Neg(a) => count_vars(a),
+        Node::Add(l, r) | Node::Sub(l, r) | Node::Mul(l, r) | Node::Div(l, r) | Node::Pow(l, r) => {
+            count_vars(l) + count_vars(r)
+        }
+        Node::Func(_, args) => args.iter().map(count_vars).sum(),
+    }
+}
+
+/// Exact range of an expression built from affine maps and monotone (or even)
+/// functions of a single occurrence of x ranging over `var`. None when a step isn't covered.
+fn composition_range(node: &Node, var: &Interval) -> Option<Interval> {
+    match node {
+        Node::Num(c) => Some(Interval::point(*c)),
+        Node::Var(_) => Some(*var),
+        Node::Neg(a) => Some(composition_range(a, var)?.scale(-1.0)),
+        Node::Add(l, r) => Some(composition_range(l, var)?.add(&composition_range(r, var)?)),
+        Node::Sub(l, r) => Some(composition_range(l, var)?.add(&composition_range(r, var)?.scale(-1.0))),
+        Node::Mul(l, r) => {
+            let (a, b) = (composition_range(l, var)?, composition_range(r, var)?);
+            if a.is_point() { Some(b.scale(a.lo)) }
+            else if b.is_point() { Some(a.scale(b.lo)) }
+            else { None }
+        }
+        Node::Div(l, r) => {
+            let (a, b) = (composition_range(l, var)?, composition_range(r, var)?);
+            if b.is_point() {
+                if b.lo == 0.0 { None } else { Some(a.scale(1.0 / b.lo)) }
+            } else if a.is_point() {
+                Some(b.reciprocal()?.scale(a.lo))
+            } else {
+                None
+            }
+        }
+        Node::Pow(base, exponent) => power_image(&composition_range(base, var)?, &composition_range(exponent, var)?),
+        Node::Func(name, args) if name == "root" && args.len() == 2 => {
+            let a = composition_range(&args[0], var)?;
+            let n = composition_range(&args[1], var)?;
+            let odd = n.is_point() && n.lo.fract() == 0.0 && n.lo % 2.0 != 0.0 && n.lo > 0.0;
+            if !odd {
+                return None;
+            }
+            let n = n.lo;
+            Some(a.map_increasing(|v| v.signum() * v.abs().powf(1.0 / n)))
+        }
+        // Clipping against a constant, e.g. max(sin(x), 0.5): the clipped side is attained
+        Node::Func(name, args) if (name == "max" || name == "min") && args.len() == 2 => {
+            let (a, b) = (composition_range(&args[0], var)?, composition_range(&args[1], var)?);
+            let (a, c) = if b.is_point() { (a, b.lo) } else if a.is_point() { (b, a.lo) } else { return None };
+            Some(match name.as_str() {
+                "max" if a.lo >= c => a,
+                "max" if a.hi <= c => Interval::point(c),
+                "max" => Interval { lo: c, lo_open: false, ..a },
+                _ if a.hi <= c => a,
+                _ if a.lo >= c => Interval::point(c),
+                _ => Interval { hi: c, hi_open: false, ..a },
+            })
+        }
+        Node::Func(name, args) if args.len() == 1 => function_image(name, &composition_range(&args[0], var)?),
+        _ => None,
+    }
+}
+
+/// Image of `a^b` when one side is a constant. None otherwise.
+fn power_image(a: &Interval, b: &Interval) -> Option<Interval> {
+    if b.is_point() {
+        let n = b.lo;
+        if n == 0.0 {
+            return Some(Interval::point(1.0));
+        }
+        if n.fract() == 0.0 && n > 0.0 {
+            return Some(if n % 2.0 == 0.0 { a.map_even(|v| v.powf(n)) } else { a.map_increasing(|v| v.powf(n)) });
+        }
+        if n.fract() == 0.0 {
+            let positive_power = if n % 2.0 == 0.0 { a.map_even(|v| v.powf(-n)) } else { a.map_increasing(|v| v.powf(-n)) };
+            return positive_power.reciprocal();
+        }
+        // Fractional powers are only real for a non-negative base
+        let base = a.intersect(&Interval::half_line(0.0, n < 0.0))?;
+        Some(if n > 0.0 { base.map_increasing(|v| v.powf(n)) } else { base.map_decreasing(|v| v.powf(n)) })
+    } else if a.is_point() && a.lo > 0.0 && a.lo != 1.0 {
+        let c = a.lo;
+        Some(if c > 1.0 { b.map_increasing(|v| c.powf(v)) } else { b.map_decreasing(|v| c.powf(v)) })
+    } else {
+        None
+    }
+}
+
+/// Image of a one-argument function over `a`. None for functions it doesn't know.
+fn function_image(name: &str, a: &Interval) -> Option<Interval> {
+    let unit = Interval { lo: -1.0, hi: 1.0, lo_open: false, hi_open: false };
+    match name {
+        "exp" => Some(a.map_increasing(f64::exp)),
+        "atan" => Some(a.map_increasing(f64::atan)),
+        "tanh" => Some(a.map_increasing(f64::tanh)),
+        "sinh" => Some(a.map_increasing(f64::sinh)),
+        "asinh" => Some(a.map_increasing(f64::asinh)),
+        "cosh" => Some(a.map_even(f64::cosh)),
+        "sech" => a.map_even(f64::cosh).reciprocal(),
+        "csch" => a.map_increasing(f64::sinh).reciprocal(),
+        "abs" => Some(a.map_even(f64::abs)),
+        "sqrt" => Some(a.intersect(&Interval::half_line(0.0, false))?.map_increasing(f64::sqrt)),
+        "ln" => Some(a.intersect(&Interval::half_line(0.0, true))?.map_increasing(f64::ln)),
+        "acosh" => Some(a.intersect(&Interval::half_line(1.0, false))?.map_increasing(f64::acosh)),
+        "asin" => Some(a.intersect(&unit)?.map_increasing(f64::asin)),
+        "acos" => Some(a.intersect(&unit)?.map_decreasing(f64::acos)),
+        "atanh" => Some(a.intersect(&Interval { lo: -1.0, hi: 1.0, lo_open: true, hi_open: true })?.map_increasing(f64::atanh)),
+        // Periodic functions: a full period in the argument covers the whole range
+        "sin" | "cos" if a.hi - a.lo >= 2.0 * PI => Some(unit),
+        "tan" if a.hi - a.lo >= PI => Some(Interval::reals()),
+        _ => None,
+    }
+}
+
+/// Natural interval extension: every occurrence of x ranges over `var`
+/// independently, so the result encloses the range (up to rounding) but may
+/// overshoot it when x repeats, as in x^2 - x. Ends are reported closed.
+fn interval_enclosure(node: &Node, var: &Interval) -> Option<Interval> {
+    let product = |a: Interval, b: Interval| {
+        let corners = [a.lo * b.lo, a.lo * b.hi, a.hi * b.lo, a.hi * b.hi];
+        if corners.iter().any(|c| c.is_nan()) {
+            return Interval::reals();
+        }
+        let lo = corners.iter().cloned().fold(f64::INFINITY, f64::min);
+        let hi = corners.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        Interval::new(lo, hi, false, false)
+    };
+    let enclosure = match node {
+        Node::Num(c) => Interval::point(*c),
+        Node::Var(_) => *var,
+        Node::Neg(a) => interval_enclosure(a, var)?.scale(-1.0),
+        Node::Add(l, r) => interval_enclosure(l, var)?.add(&interval_enclosure(r, var)?),
+        Node::Sub(l, r) => interval_enclosure(l, var)?.add(&interval_enclosure(r, var)?.scale(-1.0)),
+        Node::Mul(l, r) => product(interval_enclosure(l, var)?, interval_enclosure(r, var)?),
+        Node::Div(l, r) => product(interval_enclosure(l, var)?, interval_enclosure(r, var)?.reciprocal()?),
+        Node::Pow(base, exponent) => power_image(&interval_enclosure(base, var)?, &interval_enclosure(exponent, var)?)?,
+        // Short arguments: the endpoints, plus +-1 where a peak or trough falls inside
+        Node::Func(name, args) if (name == "sin" || name == "cos") && args.len() == 1 => {
+            let a = interval_enclosure(&args[0], var)?;
+            let shift = if name == "cos" { FRAC_PI_2 } else { 0.0 };
+            let (lo, hi) = (a.lo + shift, a.hi + shift);
+            if !(hi - lo).is_finite() || hi - lo >= 2.0 * PI {
+                return Some(Interval::new(-1.0, 1.0, false, false));
+            }
+            let reaches = |peak: f64| ((lo - peak) / (2.0 * PI)).ceil() * 2.0 * PI + peak <= hi;
+            let (s_lo, s_hi) = (lo.sin(), hi.sin());
+            Interval::new(
+                if reaches(-FRAC_PI_2) { -1.0 } else { s_lo.min(s_hi) },
+                if reaches(FRAC_PI_2) { 1.0 } else { s_lo.max(s_hi) },
+                false, false,
+            )
+        }
+        Node::Func(name, args) if args.len() == 1 => function_image(name, &interval_enclosure(&args[0], var)?)?,
+        _ => return None,
+    };
+    Some(Interval { lo_open: false, hi_open: false, ..enclosure })
+}
+
+/// Rigorous outer bound on the range over a finite `domain`: the hull of the
+/// interval extension over `subdivisions` equal slices. More slices tighten
+/// the bound toward the true range. None if the expression doesn't parse, uses
+/// an operation the extension doesn't cover, or the domain is unbounded.
+pub fn interval_range_bound(func_str: &str, domain: &Interval, subdivisions: usize) -> Option<Interval> {
+    if subdivisions == 0 || !domain.lo.is_finite() || !domain.hi.is_finite() {
+        return None;
+    }
+    let expr: Expr = rewrite_odd_roots(&canonical_variable(func_str)?).parse().ok()?;
+    let tree = build_tree(&expr)?;
+    let width = (domain.hi - domain.lo) / subdivisions as f64;
+    let parts: Vec<Interval> = (0..subdivisions)
+        .map(|i| {
+            let lo = domain.lo + i as f64 * width;
+            let hi = if i + 1 == subdivisions { domain.hi } else { lo + width };
+            interval_enclosure(&tree, &Interval::new(lo, hi, false, false))
+        })
+        .collect::<Option<_>>()?;
+    parts.into_iter().reduce(|a, b| Interval::new(a.lo.min(b.lo), a.hi.max(b.hi), false, false))
+}
+
+// =============================================================================
+// PERIODICITY
+// =============================================================================
+
+/// A period of the node read off its structure: trig of `a*x + b` repeats
+/// every base/|a|, functions of a periodic node inherit its period, and two
+/// periodic operands share the least common multiple of theirs. Not
+/// necessarily the smallest period; None for constants and aperiodic nodes.
+fn structural_period(node: &Node) -> Option<f64> {
+    let combined = |l: &Node, r: &Node| match (count_vars(l), count_vars(r)) {
+        (0, _) => structural_period(r),
+        (_, 0) => structural_period(l),
+        _ => common_period(structural_period(l)?, structural_period(r)?),
+    };
+    match node {
+        Node::Num(_) | Node::Var(_) => None,
+        Node::Neg(a) => structural_period(a),
+        Node::Add(l, r) | Node::Sub(l, r) | Node::Mul(l, r) | Node::Div(l, r) | Node::Pow(l, r) => combined(l, r),
+        Node::Func(name, args) if args.len() == 1 => {
+            let base = match name.as_str() {
+                "sin" | "cos" | "sec" | "csc" => 2.0 * PI,
+                "tan" | "cot" => PI,
+                _ => return structural_period(&args[0]),
+            };
+            match as_linear(&args[0]) {
+                Some((a, _)) if a != 0.0 => Some(base / a.abs()),
+                _ => structural_period(&args[0]),
+            }
+        }
+        Node::Func(..) => None,
+    }
+}
+
+/// Least common multiple of two periods, when their ratio is a small fraction
+fn common_period(p: f64, q: f64) -> Option<f64> {
+    let ratio = float_to_ratio(q / p, 12)?;
+    Some(p * *ratio.numer() as f64)
+}
+
+/// True when f(x + shift) matches f(x) at a spread of sample points
+fn repeats_after(func: &impl Fn(f64) -> f64, shift: f64) -> bool {
+    let mut compared = 0;
+    for i in 0..40 {
+        let x = -5.3 + 0.277 * i as f64;
+        match (safe_eval(func, x), safe_eval(func, x + shift)) {
+            (Some(a), Some(b)) if (a - b).abs() <= 1e-7 * (1.0 + a.abs()) => compared += 1,
+            (None, None) => {}
+            _ => return false,
+        }
+    }
+    compared > 0
+}
+
+/// Smallest period of a periodic expression, e.g. 2 for sin(pi*x) and pi for
+/// sin(x)^2. The structural period is checked numerically and then reduced to
+/// its smallest divisor that still repeats. None if the expression isn't periodic.
+pub fn estimate_period(func_str: &str) -> Option<f64> {
+    let expr: Expr = canonical_variable(func_str)?.parse().ok()?;
+    let period = structural_period(&build_tree(&expr)?)?;
+    let func = bind_x(expr).ok()?;
+    if !repeats_after(&func, period) {
+        return None;
+    }
+    let divisor = (1..=6).rev().find(|&k| repeats_after(&func, period / k as f64))?;
+    Some(round_to_nice(period / divisor as f64))
+}
+
+/// `(amplitude, a, b)` with amplitude > 0 if the node is a sinusoid
+/// amplitude * sin(a*x + b), cos and negation folded into the phase b
+fn as_sinusoid(node: &Node) -> Option<(f64, f64, f64)> {
+    let constant = |n: &Node| as_polynomial(n).filter(|p| p.len() == 1).map(|p| p[0]);
+    let (amp, a, b) = match node {
+        Node::Func(name, args) if args.len() == 1 && matches!(name.as_str(), "sin" | "cos") => {
+            let (a, b) = as_linear(&args[0]).filter(|&(a, _)| a != 0.0)?;
+            (1.0, a, if name == "cos" { b + FRAC_PI_2 } else { b })
+        }
+        Node::Neg(inner) => {
+            let (amp, a, b) = as_sinusoid(inner)?;
+            (-amp, a, b)
+        }
+        Node::Mul(l, r) => match (constant(l), constant(r)) {
+            (Some(c), None) => as_sinusoid(r).map(|(amp, a, b)| (c * amp, a, b))?,
+            (None, Some(c)) => as_sinusoid(l).map(|(amp, a, b)| (c * amp, a, b))?,
+            _ => return None,
+        },
+        Node::Div(l, r) => {
+            let c = constant(r).filter(|&c| c != 0.0)?;
+            as_sinusoid(l).map(|(amp, a, b)| (amp / c, a, b))?
+        }
+        _ => return None,
+    };
+    match amp {
+        amp if amp > 0.0 => Some((amp, a, b)),
+        amp if amp < 0.0 => Some((-amp, a, b + PI)),
+        _ => None,
+    }
+}
+
+/// A constant written as a decimal literal, exactly: 1.0013 is 10013/10000
+fn decimal_ratio(val: f64) -> Option<Ratio<i128>> {
+    let text = format!("{}", val.abs());
+    let (whole, frac) = text.split_once('.').unwrap_or((&text, ""));
+    if whole.len() + frac.len() > 30 {
+        return None;
+    }
+    let numer: i128 = format!("{}{}", whole, frac).parse().ok()?;
+    let ratio = Ratio::new(numer, 10_i128.pow(frac.len() as u32));
+    Some(if val < 0.0 { -ratio } else { ratio })
+}
+
+/// A constant held exactly as coef * pi^pi * e^e * sqrt(surd), surd squarefree:
+/// 1.5*pi is (3/2, 1, 0, 1) and sqrt(8)/3 is (2/3, 0, 0, 2)
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ExactConstant {
+    coef: Ratio<i128>,
+    pi: i32,
+    e: i32,
+    surd: i128,
+}
+
+impl ExactConstant {
+    fn rational(coef: Ratio<i128>) -> Self {
+        ExactConstant { coef, pi: 0, e: 0, surd: 1 }
+    }
+    
+    /// sqrt(n) as a whole number times the square root of a squarefree one;
+    /// None past 1e12, where trial division gets slow
+    fn sqrt_of(n: i128) -> Option<Self> {
+        if !(0..=1_000_000_000_000).contains(&n) {
+            return None;
+        }
+        let (mut outside, mut surd, mut p) = (1, n, 2);
+        while p * p <= surd {
+            while surd % (p * p) == 0 {
+                surd /= p * p;
+                outside *= p;
+            }
+            p += 1;
+        }
+        Some(ExactConstant { coef: Ratio::from_integer(outside), pi: 0, e: 0, surd })
+    }
+    
+    fn same_basis(&self, other: &Self) -> bool {
+        (self.pi, self.e, self.surd) == (other.pi, other.e, other.surd)
+    }
+    
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        // Both surds squarefree: their common factor comes out as a square
+        let common = gcd(self.surd, other.surd);
+        let coef = self.coef.checked_mul(&other.coef)?.checked_mul(&Ratio::from_integer(common))?;
+        let surd = (self.surd / common).checked_mul(other.surd / common)?;
+        Some(ExactConstant { coef, pi: self.pi + other.pi, e: self.e + other.e, surd })
+    }
+    
+    fn checked_div(&self, other: &Self) -> Option<Self> {
+        // 1 / (q*sqrt(m)) = sqrt(m) / (q*m)
+        let scale = other.coef.checked_mul(&Ratio::from_integer(other.surd))?;
+        let recip = ExactConstant { coef: Ratio::from_integer(1).checked_div(&scale)?, pi: -other.pi, e: -other.e, surd: other.surd };
+        self.checked_mul(&recip)
+    }
+    
+    /// Whether other / self is irrational: a rational times a nonzero power of
+    /// pi or of e (both transcendental), possibly times a surd, or a bare surd.
+    /// None when powers of both pi and e remain, which no one knows to be
+    /// irrational (pi/e, pi*e).
+    fn irrational_ratio(&self, other: &Self) -> Option<bool> {
+        let ratio = other.checked_div(self)?;
+        match (ratio.pi, ratio.e) {
+            (0, 0) => Some(ratio.surd != 1),
+            (0, _) | (_, 0) => Some(true),
+            _ => None,
+        }
+    }
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 { a.abs() } else { gcd(b, a % b) }
+}
+
+/// Exact value of a constant built from decimals, pi, e and square roots, e.g.
+/// (3/2)*pi for 1.5*pi. None for anything else (ln(2)) and anything too long
+/// to hold exactly.
+fn exact_constant(node: &Node) -> Option<ExactConstant> {
+    match node {
+        Node::Num(n) if *n == PI => Some(ExactConstant { pi: 1, ..ExactConstant::rational(Ratio::from_integer(1)) }),
+        Node::Num(n) if *n == E => Some(ExactConstant { e: 1, ..ExactConstant::rational(Ratio::from_integer(1)) }),
+        Node::Num(n) => Some(ExactConstant::rational(decimal_ratio(*n)?)),
+        Node::Func(name, args) if name == "sqrt" && args.len() == 1 => {
+            // sqrt(p/q) = sqrt(p*q) / q
+            let c = exact_constant(&args[0]).filter(|c| c.same_basis(&ExactConstant::rational(c.coef)))?;
+            let (p, q) = (*c.coef.numer(), *c.coef.denom());
+            let root = ExactConstant::sqrt_of(p.checked_mul(q)?)?;
+            root.checked_div(&ExactConstant::rational(Ratio::from_integer(q)))
+        }
+        Node::Neg(a) => exact_constant(a).map(|c| ExactConstant { coef: -c.coef, ..c }),
+        Node::Mul(l, r) => exact_constant(l)?.checked_mul(&exact_constant(r)?),
+        Node::Div(l, r) => exact_constant(l)?.checked_div(&exact_constant(r)?),
+        Node::Add(l, r) | Node::Sub(l, r) => {
+            let (a, b) = (exact_constant(l)?, exact_constant(r)?);
+            let b = if matches!(node, Node::Sub(..)) { ExactConstant { coef: -b.coef, ..b } } else { b };
+            let zero = Ratio::from_integer(0);
+            match (a.same_basis(&b), a.coef == zero, b.coef == zero) {
+                (true, _, _) => Some(ExactConstant { coef: a.coef.checked_add(&b.coef)?, ..a }),
+                (false, true, _) => Some(b),
+                (false, _, true) => Some(a),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Exact coefficient of x in a linear argument, as `exact_constant` gives it
+fn exact_frequency(node: &Node) -> Option<ExactConstant> {
+    match node {
+        Node::Var(_) => Some(ExactConstant::rational(Ratio::from_integer(1))),
+        Node::Neg(a) => exact_frequency(a).map(|c| ExactConstant { coef: -c.coef, ..c }),
+        Node::Mul(l, r) if count_vars(l) == 0 => exact_constant(l)?.checked_mul(&exact_frequency(r)?),
+        Node::Mul(l, r) if count_vars(r) == 0 => exact_frequency(&Node::Mul(r.clone(), l.clone())),
+        Node::Div(l, r) if count_vars(r) == 0 => exact_frequency(l)?.checked_div(&exact_constant(r)?),
+        Node::Add(l, r) | Node::Sub(l, r) if count_vars(r) == 0 => exact_frequency(l),
+        Node::Add(l, r) if count_vars(l) == 0 => exact_frequency(r),
+        Node::Sub(l, r) if count_vars(l) == 0 => exact_frequency(r).map(|c| ExactConstant { coef: -c.coef, ..c }),
+        _ => None,
+    }
+}
+
+/// Exact frequency of a sinusoid term, looking through constant factors
+fn sinusoid_frequency(node: &Node) -> Option<ExactConstant> {
+    match node {
+        Node::Func(name, args) if args.len() == 1 && matches!(name.as_str(), "sin" | "cos") => exact_frequency(&args[0]),
+        Node::Neg(inner) => sinusoid_frequency(inner),
+        Node::Mul(l, r) if count_vars(l) == 0 => sinusoid_frequency(r),
+        Node::Mul(l, r) | Node::Div(l, r) if count_vars(r) == 0 => sinusoid_frequency(l),
+        _ => None,
+    }
+}
+
+/// `(amplitude, a, b)` of amplitude * sin(a*x + b), as `as_sinusoid` gives it
+type Wave = (f64, f64, f64);
+
+/// c + s1 + s2 split into the offset c, the two sinusoids and the terms they
+/// came from. None for any other shape.
+fn sinusoid_pair(node: &Node) -> Option<(f64, [Wave; 2], [&Node; 2])> {
+    fn terms<'a>(node: &'a Node, sign: f64, out: &mut Vec<(f64, &'a Node)>) {
+        match node {
+            Node::Add(l, r) => { terms(l, sign, out); terms(r, sign, out); }
+            Node::Sub(l, r) => { terms(l, sign, out); terms(r, -sign, out); }
+            _ => out.push((sign, node)),
+        }
+    }
+    let mut parts = Vec::new();
+    terms(node, 1.0, &mut parts);
+    let mut offset = 0.0;
+    let mut waves = Vec::new();
+    let mut wave_terms = Vec::new();
+    for (sign, term) in parts {
+        match as_polynomial(term).filter(|p| p.len() == 1) {
+            Some(p) => offset += sign * p[0],
+            None => {
+                let (amp, a, b) = as_sinusoid(term)?;
+                waves.push((amp, a, if sign < 0.0 { b + PI } else { b }));
+                wave_terms.push(term);
+            }
+        }
+    }
+    Some((offset, waves.try_into().ok()?, wave_terms.try_into().ok()?))
+}
+
+/// Whether the two sinusoids' frequencies have an irrational ratio; None when
+/// a frequency isn't an exact constant or the ratio can't be classified
+fn frequency_ratio_irrational(terms: [&Node; 2]) -> Option<bool> {
+    sinusoid_frequency(terms[0])?.irrational_ratio(&sinusoid_frequency(terms[1])?)
+}
+
+/// True for a sum of two sinusoids whose frequency ratio is unknown, like
+/// sin(x) + sin(ln(2)*x): neither periodic nor provably dense on the torus
+fn has_unclassified_frequencies(node: &Node) -> bool {
+    sinusoid_pair(node).is_some_and(|(_, _, terms)| frequency_ratio_irrational(terms).is_none())
+}
+
+/// Exact range of c + s1 + s2 for two sinusoids whose frequencies have an
+/// irrational ratio, like sin(x) + sin(pi*x). There's no period, but the phase
+/// pairs fill the torus densely, so the range runs from c - |s1| - |s2| to
+/// c + |s1| + |s2|. An end is attained only if the two peaks (or troughs)
+/// coincide somewhere, which is checked on the first lattice points about 0.
+/// The ratio counts as irrational when it is a nonzero power of pi or of e,
+/// or a surd like sqrt(2), times a rational; anything else is left to the
+/// sampling.
+fn quasi_periodic_range(node: &Node) -> Option<Interval> {
+    let (offset, [(amp1, a1, b1), (amp2, a2, b2)], terms) = sinusoid_pair(node)?;
+    if frequency_ratio_irrational(terms) != Some(true) {
+        return None;
+    }
+    // Peaks of the first wave at a1*x + b1 = peak + 2*k*pi; the end is reached
+    // when the second wave sits on its own peak there too
+    let coincide = |peak: f64| (-1000..=1000).any(|k| {
+        let x = (peak - b1 + 2.0 * PI * k as f64) / a1;
+        ((a2 * x + b2).sin() - peak.sin()).abs() < 1e-12
+    });
+    let reach = amp1 + amp2;
+    Some(Interval::new(offset - reach, offset + reach, !coincide(-FRAC_PI_2), !coincide(FRAC_PI_2)))
+}
+
+// =============================================================================
+// RATIONAL FUNCTION ANALYSIS - Detect denominator zeros
+// =============================================================================
+
+/// Real roots of a polynomial (lowest degree first), ascending. Closed form up
+/// to degree 2; above that the roots of the derivative split [-R, R] (R the
+/// Cauchy bound) into monotone pieces, each holding at most one root, found by
+/// bisection. A root shared with the derivative (a double root) is a turning
+/// point that touches 0.
+fn poly_real_roots(poly: &[f64]) -> Vec<f64> {
+    let poly = poly_trim(poly.to_vec());
+    let mut roots = match poly.as_slice() {
+        [] | [_] => Vec::new(),
+        [b, a] => vec![-b / a],
+        [c, b, a] => {
+            let disc = b * b - 4.0 * a * c;
+            if disc < 0.0 {
+                Vec::new()
+            } else if disc == 0.0 {
+                vec![-b / (2.0 * a)]
+            } else {
+                // The root away from cancellation first, then Vieta for the other
+                let q = -0.5 * (b + b.signum() * disc.sqrt());
+                if q == 0.0 { vec![0.0] } else { vec![q / a, c / q] }
+            }
+        }
+        _ => {
+            let eval = |x: f64| poly.iter().rev().fold(0.0, |acc, c| acc * x + c);
+            let lead = poly[poly.len() - 1];
+            let bound = 1.0 + poly[..poly.len() - 1].iter().map(|c| (c / lead).abs()).fold(0.0, f64::max);
+            let derivative: Vec<f64> = poly.iter().enumerate().skip(1).map(|(i, c)| i as f64 * c).collect();
+            let scale = poly.iter().map(|c| c.abs()).fold(0.0, f64::max);
+            let mut stops = vec![-bound];
+            stops.extend(poly_real_roots(&derivative).into_iter().filter(|c| c.abs() < bound));
+            stops.push(bound);
+            let mut roots = Vec::new();
+            for w in stops.windows(2) {
+                let (mut a, mut b) = (w[0], w[1]);
+                if eval(a).abs() <= 1e-12 * scale {
+                    roots.push(a);
+                    continue;
+                }
+                if eval(a).signum() == eval(b).signum() {
+                    continue;
+                }
+                for _ in 0..200 {
+                    let m = 0.5 * (a + b);
+                    if m == a || m == b { break; }
+                    if eval(m).signum() == eval(a).signum() { a = m; } else { b = m; }
+                }
+                roots.push(0.5 * (a + b));
+            }
+            if eval(bound).abs() <= 1e-12 * scale {
+                roots.push(bound);
+            }
+            roots
+        }
+    };
+    roots.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    roots.dedup_by(|a, b| (*a - *b).abs() <= 1e-12 * a.abs().max(1.0));
+    roots
+}
+
+/// Real zeros of every polynomial denominator in the expression, at any depth
+/// (1/(2*x - 51), sin(x)/(x^2 + 300*x), exp(1/(x - 1)))
+fn polynomial_denominator_zeros(node: &Node, zeros: &mut Vec<f64>) {
+    match node {
+        Node::Num(_) | Node::Var(_) => {}
+        Node::Neg(a) => polynomial_denominator_zeros(a, zeros),
+        Node::Add(l, r) | Node::Sub(l, r) | Node::Mul(l, r) | Node::Pow(l, r) => {
+            polynomial_denominator_zeros(l, zeros);
+            polynomial_denominator_zeros(r, zeros);
+        }
+        Node::Div(l, r) => {
+            polynomial_denominator_zeros(l, zeros);
+            polynomial_denominator_zeros(r, zeros);
+            if let Some(q) = as_polynomial(r) {
+                zeros.extend(poly_real_roots(&q));
+            }
+        }
+        Node::Func(_, args) => args.iter().for_each(|arg| polynomial_denominator_zeros(arg, zeros)),
+    }
+}
+
+/// Parse a rational function to find denominator zeros (singularities)
+fn find_denominator_zeros(func_str: &str, func: &impl Fn(f64) -> f64, config: &GridConfig) -> Vec<f64> {
+    let mut zeros = Vec::new();
+    
+    // Polynomial denominators are solved exactly, wherever their roots lie
+    if let Some(tree) = func_str.parse::<Expr>().ok().and_then(|expr| build_tree(&expr)) {
+        polynomial_denominator_zeros(&tree, &mut zeros);
+    }
+    
+    // Numerical detection: scan for points where function blows up
+    let step = config.pole_scan_step;
+    let n = (config.pole_scan_radius / step).round() as i64;
+    let test_points: Vec<f64> = (-n..=n).map(|i| i as f64 * step).collect();
+    let test_values: Vec<Option<f64>> = test_points.iter().map(|&pt| safe_eval(func, pt)).collect();
+    let mut push_zero = |z: f64| {
+        if !zeros.iter().any(|&existing| (existing - z).abs() < 0.01) {
+            zeros.push(z);
+        }
+    };
+    for (i, &pt) in test_points.iter().enumerate() {
+        if test_values[i].is_none() {
+            // Check if neighbors are defined (isolated singularity)
+            let left = safe_eval(func, pt - 0.4 * step);
+            let right = safe_eval(func, pt + 0.4 * step);
+            if left.is_some() || right.is_some() {
+                // Refine the zero location
+                if let Some(z) = refine_singularity(func, pt - 2.0 * step, pt + 2.0 * step) {
+                    push_zero(z);
+                }
+            }
+        }
+    }
+    
+    // Poles strictly between test points: a sign flip, or a peak in |f|
+    for i in 1..test_points.len() {
+        let (Some(prev), Some(cur)) = (test_values[i - 1], test_values[i]) else { continue };
+        if prev.signum() != cur.signum() {
+            if let Some(z) = pole_between(func, test_points[i - 1], test_points[i]) {
+                push_zero(z);
+            }
+        } else if let Some(Some(next)) = test_values.get(i + 1) {
+            if cur.abs() >= prev.abs() && cur.abs() > next.abs() && cur.signum() == next.signum() {
+                if let Some(z) = pole_between(func, test_points[i - 1], test_points[i + 1]) {
+                    push_zero(z);
+                }
+            }
+        }
+    }
+    
+    // Clean up zeros (round to nice values)
+    zeros.iter().map(|&z| round_to_nice(z)).collect()
+}
+
+/// Pole between two valid points: odd-order poles flip the sign (bisected),
+/// even-order ones peak in |f| (ternary search). Either way |f| has to grow by
+/// orders of magnitude as the bracket closes, which a root, jump or smooth
+/// bump never does.
+fn pole_between(func: &impl Fn(f64) -> f64, mut a: f64, mut b: f64) -> Option<f64> {
+    let (fa, fb) = (safe_eval(func, a)?, safe_eval(func, b)?);
+    let edge = fa.abs().max(fb.abs()).max(1.0);
+    if fa.signum() != fb.signum() {
+        for _ in 0..60 {
+            let m = 0.5 * (a + b);
+            match safe_eval(func, m) {
+                None => return Some(m),
+                Some(fm) if fm.signum() == fa.signum() => a = m,
+                Some(_) => b = m,
+            }
+        }
+    } else {
+        for _ in 0..100 {
+            let (m1, m2) = (a + (b - a) / 3.0, b - (b - a) / 3.0);
+            match (safe_eval(func, m1), safe_eval(func, m2)) {
+                (None, _) => return Some(m1),
+                (_, None) => return Some(m2),
+                (Some(f1), Some(f2)) => if f1.abs() < f2.abs() { a = m1 } else { b = m2 },
+            }
+        }
+    }
+    let peak = safe_eval(func, a)?.abs().min(safe_eval(func, b)?.abs());
+    if peak > 1e6 * edge { Some(0.5 * (a + b)) } else { None }
+}
+
+/// Refine singularity location using bisection
+fn refine_singularity(func: &impl Fn(f64) -> f64, mut lo: f64, mut hi: f64) -> Option<f64> {
+    for _ in 0..50 {
+        let mid = (lo + hi) / 2.0;
+        if safe_eval(func, mid).is_none() {
+            // Singularity is at or near mid
+            if safe_eval(func, lo).is_some() {
+                hi = mid;
+            } else if safe_eval(func, hi).is_some() {
+                lo = mid;
+            } else {
+                return Some(mid);
+            }
+        } else {
+            // Mid is defined, singularity must be elsewhere
+            if safe_eval(func, lo).is_none() {
+                hi = mid;
+            } else if safe_eval(func, hi).is_none() {
+                lo = mid;
+            } else {
+                return None; // No singularity in this range
+            }
+        }
+    }
+    Some((lo + hi) / 2.0)
+}
+
+/// Round to nice mathematical values
+fn round_to_nice(val: f64) -> f64 {
+    // Check for integers
+    let rounded_int = val.round();
+    if (val - rounded_int).abs() < 1e-9 {
+        return rounded_int;
+    }
+    
+    // Check for common fractions
+    for denom in [2, 3, 4, 5, 6, 8, 10] {
+        let numer = (val * denom as f64).round();
+        if (val - numer / denom as f64).abs() < 1e-9 {
+            return numer / denom as f64;
+        }
+    }
+    
+    // Check for sqrt values
+    for base in [2, 3, 5] {
+        let sqrt_base = (base as f64).sqrt();
+        if (val - sqrt_base).abs() < 1e-9 { return sqrt_base; }
+        if (val + sqrt_base).abs() < 1e-9 { return -sqrt_base; }
+    }
+    
+    val
+}
+
+// =============================================================================
+// HORIZONTAL ASYMPTOTE DETECTION (for excluded range values)
+// =============================================================================
+
+/// Aitken's delta-squared estimate of the limit of the last three samples.
+/// Applied only when the tail converges geometrically, otherwise the last sample.
+fn extrapolate_tail(samples: &[f64]) -> f64 {
+    let n = samples.len();
+    let last = samples[n - 1];
+    if n < 3 {
+        return last;
+    }
+    let d1 = samples[n - 2] - samples[n - 3];
+    let d2 = last - samples[n - 2];
+    let ratio = d2 / d1;
+    if d1 != 0.0 && ratio > 0.0 && ratio < 0.5 {
+        last + d2 * ratio / (1.0 - ratio)
+    } else {
+        last
+    }
+}
+
+/// Find horizontal asymptotes (values the function approaches but never reaches)
+fn find_horizontal_asymptotes(func: &impl Fn(f64) -> f64, config: &SolverConfig) -> Vec<f64> {
+    let mut asymptotes = Vec::new();
+    // A decaying wiggle ((2 + sin(x))/x) leaves the extrapolation a hair off 0
+    let settle = |tail: &[f64]| {
+        let limit = round_to_nice(extrapolate_tail(tail));
+        if limit.abs() < 1e-6 { 0.0 } else { limit }
+    };
+    
+    // Far samples first; if they overflow (exp(x)/(1 + exp(x)) is inf/inf past
+    // x = 709) the tail is read off a nearer run instead
+    let tail_samples = |sign: f64| {
+        let far: Vec<f64> = [1e3, 1e4, 1e5, 1e6, 1e7, 1e8].iter().filter_map(|&x| safe_eval(func, sign * x)).collect();
+        if far.len() >= 3 {
+            return far;
+        }
+        [25.0, 50.0, 100.0, 200.0, 400.0].iter().filter_map(|&x| safe_eval(func, sign * x)).collect()
+    };
+    
+    // Check limit as x -> +oo
+    let pos_inf_samples = tail_samples(1.0);
+    
+    if pos_inf_samples.len() >= 3 {
+        let last = pos_inf_samples.last().unwrap();
+        let second_last = pos_inf_samples.get(pos_inf_samples.len() - 2).unwrap();
+        if (last - second_last).abs() < 0.001 && last.abs() < config.inf_threshold {
+            asymptotes.push(settle(&pos_inf_samples));
+        }
+    }
+    
+    // Check limit as x -> -oo
+    let neg_inf_samples = tail_samples(-1.0);
+    
+    if neg_inf_samples.len() >= 3 {
+        let last = neg_inf_samples.last().unwrap();
+        let second_last = neg_inf_samples.get(neg_inf_samples.len() - 2).unwrap();
+        if (last - second_last).abs() < 0.001 && last.abs() < config.inf_threshold {
+            let asym = settle(&neg_inf_samples);
+            if !asymptotes.iter().any(|&a| (a - asym).abs() < 0.001) {
+                asymptotes.push(asym);
+            }
+        }
+    }
+    
+    asymptotes
+}
+
+/// Check if a value is achievable by the function
+fn is_value_achievable(func: &impl Fn(f64) -> f64, target: f64, domain_zeros: &[f64]) -> bool {
+    if !target.is_finite() {
+        return false;
+    }
+    
+    // Dense search
+    let step = 0.001;
+    let mut prev: Option<(f64, f64)> = None;
+    let (mut on_plateau, mut rounded_edge) = (false, false);
+    for i in -100000..=100000 {
+        let x = i as f64 * step;
+        
+        // Skip domain exclusions
+        if domain_zeros.iter().any(|&z| (x - z).abs() < 0.001) {
+            prev = None;
+            continue;
+        }
+        
+        // A sign change between neighbours is a hit if bisection closes in on
+        // the target rather than on a jump (sin(x)/x crosses 0 at pi)
+        let y = safe_eval(func, x);
+        if let (Some((x0, y0)), Some(y1)) = (prev, y) {
+            if (y0 - target) * (y1 - target) < 0.0 && crossing_attained(func, target, x0, x) {
+                return true;
+            }
+        }
+        prev = y.map(|y| (x, y));
+        
+        // A hit must cross or touch the target; a tail creeping toward it
+        // (1/sinh(x) near x = 100) keeps getting closer past the hit
+        if let Some(y) = y {
+            if (y - target).abs() < 1e-8 {
+                let gap = |x: f64| safe_eval(func, x).map(|v| v - target);
+                let (left, right) = (gap(x - step), gap(x + step));
+                // Gaps within a few ulps of the target are rounding, not signal: a
+                // tail rounded onto its asymptote (exp(x)/(1 + exp(x)) is 1.0 past
+                // x = 37) must not pass for a real plateau like min(x, 0)'s
+                let res = 4.0 * f64::EPSILON * target.abs();
+                let d = y - target;
+                if d == 0.0 {
+                    if [left, right].iter().flatten().any(|g| g.abs() > res) {
+                        return true;
+                    }
+                    on_plateau = true;
+                    rounded_edge |= [left, right].iter().flatten().any(|&g| g != 0.0);
+                    continue;
+                }
+                let crosses = d.abs() > res && [left, right].iter().flatten().any(|&g| g * d < 0.0 && g.abs() > res);
+                let touches = [left, right].iter().all(|g| g.is_none_or(|g| g.abs() > d.abs() + res));
+                if crosses || touches {
+                    return true;
+                }
+            }
+        }
+    }
+    
+    // Flat on the target all along, as for a constant
+    on_plateau && !rounded_edge
+}
+
+/// Bisect a bracket where f - target changes sign; true if f gets within reach
+/// of the target at the crossing instead of jumping over it
+fn crossing_attained(func: &impl Fn(f64) -> f64, target: f64, mut lo: f64, mut hi: f64) -> bool {
+    let Some(lo_val) = safe_eval(func, lo) else { return false };
+    let lo_sign = (lo_val - target).signum();
+    for _ in 0..60 {
+        let mid = 0.5 * (lo + hi);
+        let Some(v) = safe_eval(func, mid) else { return false };
+        if (v - target).signum() == lo_sign { lo = mid; } else { hi = mid; }
+    }
+    [lo, hi].iter().filter_map(|&x| safe_eval(func, x)).any(|v| (v - target).abs() < 1e-6)
+}
+
+// =============================================================================
+// BRENT'S METHOD FOR OPTIMIZATION
+// =============================================================================
+fn brent_minimize<F>(func: F, a: f64, b: f64, find_max: bool, config: &SolverConfig) -> Option<(f64, f64)>
+where
+    F: Fn(f64) -> f64,
+{
+    let f = |x: f64| -> f64 {
+        let val = func(x);
+        if find_max { -val } else { val }
+    };
+    
+    let golden = 0.381966011250105;
+    let mut a = a;
+    let mut b = b;
+    let mut x = a + golden * (b - a);
+    let mut w = x;
+    let mut v = x;
+    
+    let mut fx = f(x);
+    if !is_valid(fx) { return None; }
+    let mut fw = fx;
+    let mut fv = fx;
+    
+    let mut d: f64 = 0.0;
+    let mut e: f64 = 0.0;
+    
+    for _ in 0..config.max_brent_iterations {
+        let midpoint = 0.5 * (a + b);
+        let tol1 = config.brent_tolerance * x.abs() + 1e-10;
+        let tol2 = 2.0 * tol1;
+        
+        if (x - midpoint).abs() <= tol2 - 0.5 * (b - a) {
+            let result = if find_max { -fx } else { fx };
+            return Some((x, result));
+        }
+        
+        let u;
+        if e.abs() > tol1 {
+            let r = (x - w) * (fx - fv);
+            let mut q = (x - v) * (fx - fw);
+            let mut p = (x - v) * q - (x - w) * r;
+            q = 2.0 * (q - r);
+            if q > 0.0 { p = -p; } else { q = -q; }
+            
+            let r_old = e;
+            e = d;
+            
+            if p.abs() < (0.5 * q * r_old).abs() && p > q * (a - x) && p < q * (b - x) {
+                d = p / q;
+                u = x + d;
+                if (u - a) < tol2 || (b - u) < tol2 {
+                    d = if x < midpoint { tol1 } else { -tol1 };
+                }
+            } else {
+                e = if x < midpoint { b - x } else { a - x };
+                d = golden * e;
+            }
+        } else {
+            e = if x < midpoint { b - x } else { a - x };
+            d = golden * e;
+        }
+        
+        let u_new = if d.abs() >= tol1 { x + d } else { x + tol1 * d.signum() };
+        let fu = f(u_new);
+        if !is_valid(fu) { continue; }
+        
+        if fu <= fx {
+            if u_new < x { b = x; } else { a = x; }
+            v = w; fv = fw;
+            w = x; fw = fx;
+            x = u_new; fx = fu;
+        } else {
+            if u_new < x { a = u_new; } else { b = u_new; }
+            if fu <= fw || w == x {
+                v = w; fv = fw;
+                w = u_new; fw = fu;
+            } else if fu <= fv || v == x || v == w {
+                v = u_new; fv = fu;
+            }
+        }
+    }
+    
+    let result = if find_max { -fx } else { fx };
+    Some((x, result))
+}
+
+/// Brent minimum and maximum over `windows` equal slices of [lo, hi], the slices
+/// optimized in parallel with one parsed expression per thread (in order without
+/// the `rayon` feature). Points outside
+/// `domain` count as undefined; `config` sets Brent's tolerance and iteration
+/// cap. None if the expression doesn't parse.
+pub fn brent_sweep(func_str: &str, domain: &Domain, lo: f64, hi: f64, windows: usize, config: &SolverConfig) -> Option<(f64, f64)> {
+    // Validate once; a thread whose bind still fails contributes no extremes
+    let parsed: Expr = func_str.parse().ok()?;
+    let _ = bind_x(parsed.clone()).ok()?;
+    let width = (hi - lo) / windows as f64;
+    let slices: Vec<usize> = (0..windows).collect();
+    let extremes = map_init(
+        &slices,
+        || bind_x(parsed.clone()).ok(),
+        |f, &i| {
+            let Some(f) = f.as_ref() else { return (f64::INFINITY, f64::NEG_INFINITY) };
+            let masked = |x: f64| if domain.contains(x) { f(x) } else { f64::NAN };
+            let a = lo + (i as f64) * width;
+            let min = brent_minimize(masked, a, a + width, false, config).map_or(f64::INFINITY, |(_, val)| val);
+            let max = brent_minimize(masked, a, a + width, true, config).map_or(f64::NEG_INFINITY, |(_, val)| val);
+            (min, max)
+        },
+    );
+    Some(extremes.into_iter().fold((f64::INFINITY, f64::NEG_INFINITY), |p, q| (p.0.min(q.0), p.1.max(q.1))))
+}
+
+/// Single-threaded reference for `brent_sweep`; visits the slices in order
+pub fn brent_sweep_sequential(func_str: &str, domain: &Domain, lo: f64, hi: f64, windows: usize, config: &SolverConfig) -> Option<(f64, f64)> {
+    let f = bind_x(func_str.parse::<Expr>().ok()?).ok()?;
+    let masked = |x: f64| if domain.contains(x) { f(x) } else { f64::NAN };
+    let width = (hi - lo) / windows as f64;
+    let mut extremes = (f64::INFINITY, f64::NEG_INFINITY);
+    for i in 0..windows {
+        let a = lo + (i as f64) * width;
+        if let Some((_, val)) = brent_minimize(masked, a, a + width, false, config) {
+            extremes.0 = extremes.0.min(val);
+        }
+        if let Some((_, val)) = brent_minimize(masked, a, a + width, true, config) {
+            extremes.1 = extremes.1.max(val);
+        }
+    }
+    Some(extremes)
+}
+
+// =============================================================================
+// LIMIT ANALYSIS
+// =============================================================================
+fn analyze_limit(func: &impl Fn(f64) -> f64, toward: f64, config: &SolverConfig) -> Option<f64> {
+    // Probes go out as far as the solver's idea of infinity
+    let sign = if toward == f64::INFINITY {
+        1.0
+    } else if toward == f64::NEG_INFINITY {
+        -1.0
+    } else {
+        return None;
+    };
+    let sequence: Vec<f64> = [1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10].into_iter()
+        .filter(|&x| x < config.inf_threshold)
+        .chain([config.inf_threshold])
+        .map(|x| sign * x)
+        .collect();
+    
+    let raw: Vec<f64> = sequence.iter().map(|&x| func(x)).collect();
+    let vals: Vec<f64> = raw.iter().cloned().filter(|&v| is_valid(v)).collect();
+    
+    // Overflow (exp(x) at 1e3) is divergence too, provided the finite values
+    // before it already grow with the same sign
+    if let Some(first_inf) = raw.iter().position(|v| v.is_infinite()) {
+        let sign = raw[first_inf].signum();
+        let before: Vec<f64> = raw[..first_inf].iter().cloned().filter(|&v| is_valid(v)).collect();
+        if before.iter().all(|v| v.signum() == sign) && before.windows(2).all(|w| w[1].abs() >= w[0].abs()) {
+            return Some(raw[first_inf]);
+        }
+    }
+    
+    if vals.len() < 3 { return None; }
+    
+    // Divergence is judged on the far half of the sequence, where a bounded
+    // oscillation riding on the growth (sin(x) + x/100) no longer breaks the trend
+    let tail = &vals[vals.len() / 2..];
+    
+    // Check for divergence to +infinity
+    if tail.windows(2).all(|w| w[1] > w[0] * 0.9) && vals.last().map(|&v| v > 1e10).unwrap_or(false) {
+        return Some(f64::INFINITY);
+    }
+    
+    // Check for divergence to -infinity
+    if tail.windows(2).all(|w| w[1] < w[0] * 0.9) && vals.last().map(|&v| v < -1e10).unwrap_or(false) {
+        return Some(f64::NEG_INFINITY);
+    }
+    
+    // Check for convergence to finite value
+    let last_vals: Vec<f64> = vals.iter().rev().take(4).cloned().collect();
+    if last_vals.len() >= 3 {
+        let min_val = last_vals.iter().cloned().fold(f64::INFINITY, f64::min);
+        let max_val = last_vals.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        if (max_val - min_val).abs() < 0.01 {
+            return Some((min_val + max_val) / 2.0);
+        }
+    }
+    
+    None
+}
+
+/// +/-oo if the function blows up as x -> z from the given side (-1 left, 1 right).
+/// Magnitudes must keep growing with a fixed sign as the probes close in, which
+/// catches simple poles (~1/eps) that never reach an absolute threshold.
+fn pole_divergence(func: &impl Fn(f64) -> f64, z: f64, side: f64) -> Option<f64> {
+    let vals: Vec<f64> = [1e-3, 1e-5, 1e-7, 1e-9, 1e-11].iter()
+        .filter_map(|&eps| safe_eval(func, z + side * eps))
+        .collect();
+    if vals.len() < 3 { return None; }
+    
+    let growing = vals.windows(2).all(|w| w[1].abs() > 10.0 * w[0].abs() && w[1].signum() == w[0].signum());
+    let last = vals[vals.len() - 1];
+    if growing && last.abs() > 1e6 {
+        Some(last.signum() * f64::INFINITY)
+    } else {
+        None
+    }
+}
+
+/// Finite value the function settles on as x -> z from the given side, for
+/// poles of an inner function that a bounded outer one tames (atan(1/x) at 0)
+fn pole_side_limit(func: &impl Fn(f64) -> f64, z: f64, side: f64) -> Option<f64> {
+    let vals: Vec<f64> = [1e-5, 1e-7, 1e-9].iter()
+        .filter_map(|&eps| safe_eval(func, z + side * eps))
+        .collect();
+    if vals.len() < 3 || (vals[2] - vals[1]).abs() > 1e-6 || (vals[1] - vals[0]).abs() > 1e-3 {
+        return None;
+    }
+    Some(round_to_nice(extrapolate_tail(&vals)))
+}
+
+/// Whether the (max, min) of an oscillation with no limit keep growing toward
+/// an infinite end. Windows of equal width, each spanning many unit-scale periods,
+/// sit one decade further out each time: a bounded function (sin(x)*cos(x))
+/// repeats the same extremes, an unbounded one (x*sin(x), ln(1+x^2)*sin(x))
+/// climbs by at least a percent per decade.
+fn envelope_growth(func: &impl Fn(f64) -> f64, end: f64) -> (bool, bool) {
+    let side = end.signum();
+    let mut tops = Vec::new();
+    let mut bottoms = Vec::new();
+    for k in 1..=8 {
+        let start = 10f64.powi(k);
+        let (lo, hi) = (0..2000)
+            .filter_map(|i| safe_eval(func, side * (start + 0.05 * i as f64)))
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), y| (lo.min(y), hi.max(y)));
+        if lo > hi { continue; }
+        tops.push(hi);
+        bottoms.push(-lo);
+    }
+    if tops.len() < 4 { return (false, false); }
+
+    let climbs = |v: &[f64]| v[v.len() - 1] > 0.0 && v.windows(2).all(|w| w[1] > w[0] + 0.01 * w[0].abs());
+    (climbs(&tops), climbs(&bottoms))
+}
+
+// =============================================================================
+// RATIONAL END BEHAVIOR - Degree comparison of numerator/denominator
+// =============================================================================
+
+/// Behavior of p(x)/q(x) as x -> +/-oo
+#[derive(Debug, Clone, PartialEq)]
+enum RationalAsymptote {
+    /// deg p <= deg q: y -> value
+    Horizontal(f64),
+    /// deg p = deg q + 1: y ~ slope*x + intercept
+    Oblique { slope: f64, intercept: f64 },
+    /// deg p > deg q + 1
+    Unbounded,
+}
+
+/// Split a top-level `p/q` into numerator and denominator polynomials
+fn rational_parts(node: &Node) -> Option<(Vec<f64>, Vec<f64>)> {
+    match node {
+        Node::Div(p, q) => {
+            let (p, q) = (as_polynomial(p)?, as_polynomial(q)?);
+            if q.len() < 2 { None } else { Some((p, q)) }
+        }
+        _ => None,
+    }
+}
+
+fn rational_asymptote(p: &[f64], q: &[f64]) -> RationalAsymptote {
+    let (deg_p, deg_q) = (p.len() - 1, q.len() - 1);
+    let lead_q = q[deg_q];
+    if deg_p < deg_q {
+        RationalAsymptote::Horizontal(0.0)
+    } else if deg_p == deg_q {
+        RationalAsymptote::Horizontal(p[deg_p] / lead_q)
+    } else if deg_p == deg_q + 1 {
+        // Leading two terms of the polynomial long division
+        let slope = p[deg_p] / lead_q;
+        let intercept = (p[deg_p - 1] - slope * q.get(deg_q.wrapping_sub(1)).copied().unwrap_or(0.0)) / lead_q;
+        RationalAsymptote::Oblique { slope, intercept }
+    } else {
+        RationalAsymptote::Unbounded
+    }
+}
+
+/// Exact limit of p(x)/q(x) toward +oo or -oo
+fn rational_limit(p: &[f64], q: &[f64], toward: f64) -> f64 {
+    match rational_asymptote(p, q) {
+        RationalAsymptote::Horizontal(value) => value,
+        _ => {
+            // Sign of the leading term (p_n/q_m) * x^(n-m) for large |x|
+            let excess = (p.len() - q.len()) as i32;
+            let lead = p[p.len() - 1] / q[q.len() - 1];
+            let sign = lead.signum() * toward.signum().powi(excess);
+            sign * f64::INFINITY
+        }
+    }
+}
+
+// =============================================================================
+// RANGE GAPS - Values skipped between branches (poles, jumps)
+// =============================================================================
+
+/// Gaps in the sampled values that survive local refinement. Sorting the values
+/// exposes candidates, the steps widest against the local sampling density
+/// first; a candidate is only reported when every pair of
+/// neighboring samples straddling it bisects down to a jump (pole or step)
+/// rather than into the gap, so sparse sampling of a steep piece is not a gap.
+fn detect_range_gaps(func: &impl Fn(f64) -> f64, samples: &[(f64, f64)], config: &GridConfig) -> Vec<(f64, f64)> {
+    const MAX_EDGE: f64 = 1e4;
+    const MAX_CANDIDATES: usize = 20;
+    // Steps on either side that set the local sampling density
+    const DENSITY_WINDOW: usize = 8;
+    
+    let scale = output_scale(samples, config);
+    let mut ys: Vec<f64> = samples.iter().map(|&(_, y)| y).filter(|y| y.abs() <= MAX_EDGE * scale).collect();
+    ys.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    // Width relative to the magnitude of the edges, so steep branches far from
+    // the origin don't crowd out a narrow gap near it
+    let relative_width = |&(lo, hi): &(f64, f64)| (hi - lo) / lo.abs().max(hi.abs()).max(scale);
+    // Ranked by how far a step stands out from the sampling density around it:
+    // the log-spaced tail is uniformly sparse, a skipped band is not
+    let deltas: Vec<f64> = ys.windows(2).map(|w| w[1] - w[0]).collect();
+    let prominence = |i: usize| {
+        let (start, end) = (i.saturating_sub(DENSITY_WINDOW), (i + DENSITY_WINDOW + 1).min(deltas.len()));
+        let mut local: Vec<f64> = (start..end).filter(|&j| j != i).map(|j| deltas[j]).collect();
+        if local.is_empty() { return f64::INFINITY; }
+        let mid = local.len() / 2;
+        let typical = *local.select_nth_unstable_by(mid, |a, b| a.partial_cmp(b).unwrap()).1;
+        if typical > 0.0 { deltas[i] / typical } else { f64::INFINITY }
+    };
+    let mut candidates: Vec<(f64, (f64, f64))> = (0..deltas.len())
+        .map(|i| (prominence(i), (ys[i], ys[i + 1])))
+        .filter(|(_, gap)| relative_width(gap) > config.gap_tolerance)
+        .collect();
+    candidates.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+    candidates.truncate(MAX_CANDIDATES);
+    
+    let mut gaps: Vec<(f64, f64)> = candidates.into_iter()
+        .map(|(_, gap)| gap)
+        .filter(|&(lo, hi)| !gap_is_filled(func, samples, lo, hi))
+        .collect();
+    gaps.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    gaps
+}
+
+/// Midpoints of the runs of sorted values that stay within `tolerance` of the
+/// run's first value, relative to its magnitude (absolute below 1), so a level
+/// sampled with noise collapses to its centre
+fn cluster_levels(sorted: &[f64], tolerance: f64) -> Vec<f64> {
+    let mut levels = Vec::new();
+    let mut start = 0;
+    for i in 1..=sorted.len() {
+        let first = sorted[start];
+        if i == sorted.len() || sorted[i] - first > tolerance * first.abs().max(sorted[i].abs()).max(1.0) {
+            levels.push(round_to_nice(0.5 * (sorted[start] + sorted[i - 1])));
+            start = i;
+        }
+    }
+    levels
+}
+
+/// Typical output magnitude: the median |f(x)| over the pole scan window, which
+/// unlike the spread stays finite next to poles. 1 for a function that is 0 there.
+fn output_scale(samples: &[(f64, f64)], config: &GridConfig) -> f64 {
+    let mut magnitudes: Vec<f64> = samples.iter()
+        .filter(|&&(x, _)| x.abs() <= config.pole_scan_radius)
+        .map(|&(_, y)| y.abs())
+        .collect();
+    if magnitudes.is_empty() {
+        return 1.0;
+    }
+    let mid = magnitudes.len() / 2;
+    let median = *magnitudes.select_nth_unstable_by(mid, |a, b| a.partial_cmp(b).unwrap()).1;
+    if median > 0.0 { median } else { 1.0 }
+}
+
+/// Corroboration: bisect every sample pair that straddles the gap's midpoint and
+/// report whether any refined value lands strictly inside the gap
+fn gap_is_filled(func: &impl Fn(f64) -> f64, samples: &[(f64, f64)], lo: f64, hi: f64) -> bool {
+    let mid = 0.5 * (lo + hi);
+    for pair in samples.windows(2) {
+        let ((mut a, ya), (mut b, yb)) = (pair[0], pair[1]);
+        if (ya < mid) == (yb < mid) {
+            continue;
+        }
+        let a_below = ya < mid;
+        for _ in 0..80 {
+            let m = 0.5 * (a + b);
+            let y = func(m);
+            if !is_valid(y) {
+                break;
+            }
+            if y > lo && y < hi {
+                return true;
+            }
+            if (y < mid) == a_below { a = m; } else { b = m; }
+            if b - a <= 1e-14 * a.abs().max(1.0) {
+                break;
+            }
+        }
+    }
+    false
+}
+
+/// Polish a gap edge: the sampled extreme of the branch next to the gap is a
+/// local max (below the gap) or min (above it) near the sample that produced it.
+/// Also says whether the edge is attained: one that sits on a jump, like 1/2 for
+/// floor(x)/x as x rises to 2, is only a one-sided limit unless the function
+/// takes that value at the jump itself or stays on it along a flat step.
+fn refine_gap_edge(func: &impl Fn(f64) -> f64, samples: &[(f64, f64)], edge: f64, find_max: bool, config: &SolverConfig) -> (f64, bool) {
+    let Some(&(x, _)) = samples.iter().find(|&&(_, y)| y == edge) else {
+        return (edge, true);
+    };
+    let (at, val) = match brent_minimize(func, x - 0.01, x + 0.01, find_max, config) {
+        Some((at, val)) if find_max && val > edge && (val - edge) < 1e-3 => (at, val),
+        Some((at, val)) if !find_max && val < edge && (edge - val) < 1e-3 => (at, val),
+        _ => (x, edge),
+    };
+    // A flat step next to the jump takes the edge value all along it
+    let scale = val.abs().max(1.0);
+    let jump = match (safe_eval(func, at - 1e-6), safe_eval(func, at + 1e-6)) {
+        (Some(left), Some(right)) => {
+            let flat = (left - val).abs() < 1e-12 * scale || (right - val).abs() < 1e-12 * scale;
+            (left - right).abs() > 1e-3 * scale && !flat
+        }
+        _ => false,
+    };
+    let attained = !jump || safe_eval(func, round_to_nice(at)).is_some_and(|y| (y - val).abs() < 1e-6 * scale);
+    (val, attained)
+}
+
+/// What is left of a gap once the values an end tail sweeps through are taken
+/// out of it; None when a tail covers it. A tail strictly inside the gap would
+/// split it and is left alone.
+fn trim_gap_by_tails((mut lo, mut hi): (f64, f64), tails: &[(f64, f64)]) -> Option<(f64, f64)> {
+    for &(a, b) in tails {
+        let covers_lo = a <= lo && b > lo;
+        let covers_hi = a < hi && b >= hi;
+        match (covers_lo, covers_hi) {
+            (true, true) => return None,
+            (true, false) => lo = b,
+            (false, true) => hi = a,
+            (false, false) => {}
+        }
+    }
+    Some((lo, hi))
+}
+
+/// Split [min, max] into the pieces left between confirmed gaps. Gap edges are
+/// attained unless they are an unreached asymptote; excluded values inside a
+/// piece split it further.
+fn range_parts_with_gaps(bounds: Interval, gaps: &[(f64, f64)], excluded_range_values: &[f64], tolerance: f64, scale: f64) -> Vec<Interval> {
+    let is_excluded = |v: f64| excluded_range_values.iter().any(|&e| (e - v).abs() < 1e-9);
+    let Interval { lo: min, hi: max, lo_open: min_open, hi_open: max_open } = bounds;
+    
+    // An edge within `tolerance` of an unreached asymptote, relative to the
+    // asymptote or the output `scale` when it is near 0, is that asymptote
+    let snap = |v: f64| excluded_range_values.iter()
+        .find(|&&e| (e - v).abs() <= tolerance * e.abs().max(scale))
+        .copied()
+        .unwrap_or_else(|| round_to_nice(v));
+    let mut cuts: Vec<(f64, f64)> = gaps.iter().map(|&(lo, hi)| (snap(lo), snap(hi))).collect();
+    for &v in excluded_range_values {
+        if v > min && v < max && !cuts.iter().any(|&(lo, hi)| v >= lo && v <= hi) {
+            cuts.push((v, v));
+        }
+    }
+    cuts.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+    
+    let mut parts = Vec::new();
+    let (mut lo, mut lo_open) = (min, min_open);
+    for (gap_lo, gap_hi) in cuts {
+        parts.push(Interval::new(lo, gap_lo, lo_open, gap_lo == gap_hi || is_excluded(gap_lo)));
+        lo = gap_hi;
+        lo_open = gap_lo == gap_hi || is_excluded(gap_hi);
+    }
+    parts.push(Interval::new(lo, max, lo_open, max_open));
+    parts
+}
+
+/// Exterior range of a function with periodic poles, like csc or sec: every
+/// branch between consecutive poles keeps one sign, and the smallest |f| over
+/// the branches is the bound. The bound is closed when the search lands on it
+/// inside a branch rather than creeping toward a branch end.
+fn periodic_exterior(func: &impl Fn(f64) -> f64, poles: &[f64], config: &SolverConfig) -> Option<(f64, bool)> {
+    const EDGE: f64 = 1e-6;
+    let mut poles = poles.to_vec();
+    poles.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let (mut has_pos, mut has_neg) = (false, false);
+    let mut bound = f64::INFINITY;
+    let mut attained = false;
+    for w in poles.windows(2) {
+        let (a, b) = (w[0] + EDGE, w[1] - EDGE);
+        let positive = safe_eval(func, 0.5 * (a + b))? > 0.0;
+        let (x, val) = brent_minimize(func, a, b, !positive, config)?;
+        // A branch crossing zero, as with tan or cot, has no exterior bound
+        if (val > 0.0) != positive || val == 0.0 {
+            return None;
+        }
+        if positive { has_pos = true } else { has_neg = true }
+        let interior = x - a > 1e-4 && b - x > 1e-4;
+        if val.abs() < bound - 1e-9 {
+            bound = val.abs();
+            attained = interior;
+        } else if (val.abs() - bound).abs() <= 1e-9 {
+            attained &= interior;
+        }
+    }
+    (has_pos && has_neg).then(|| (round_to_nice(bound), attained))
+}
+
+// =============================================================================
+// DOMAIN DETECTION - IMPROVED with rational function analysis
+// =============================================================================
+/// Cheap entry point: parse, bind and detect the domain without any range analysis
+pub fn detect_domain_only(func_str: &str) -> Result<Domain, SolveError> {
+    let func_str = &canonical_variable(func_str)
+        .map(|s| rewrite_odd_roots(&s))
+        .ok_or_else(|| SolveError::Bind("expected a single variable".to_string()))?;
+    let expr: Expr = func_str.parse().map_err(|e: meval::Error| SolveError::Parse(e.to_string()))?;
+    let func = bind_x(expr).map_err(|e| SolveError::Bind(e.to_string()))?;
+    Ok(detect_domain(func_str, &func, &GridConfig::default()))
+}
+
+/// Vertical asymptotes and other isolated singularities within the pole scan
+/// window ([-10, 10] by default), sorted. Periodic poles such as tan's are
+/// listed exactly from their lattice; the rest come from the numerical scan.
+pub fn find_singularities(func_str: &str) -> Vec<f64> {
+    let config = GridConfig::default();
+    let Some(func_str) = canonical_variable(func_str).map(|s| rewrite_odd_roots(&s)) else { return Vec::new() };
+    let Ok(func) = func_str.parse::<Expr>().and_then(bind_x) else { return Vec::new() };
+    let mut poles = find_denominator_zeros(&func_str, &func, &config);
+    if let Domain::PeriodicComplement { pattern } = detect_domain(&func_str, &func, &config) {
+        let radius = config.pole_scan_radius;
+        if let Some(lattice) = lattice_points(&pattern, -radius, radius) {
+            poles.retain(|&p| !lattice.iter().any(|&q| (p - q).abs() < 0.01));
+            poles.extend(lattice);
+        }
+    }
+    poles.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    poles
+}
+
+/// Points of a `PeriodicComplement` pattern inside [lo, hi]: the two-sided
+/// `ImageSet(Lambda(_n, pi/2 + _n*pi), Integers)`, or gamma's one-sided
+/// `Range(0, -oo, -1)` and `ImageSet(Lambda(_n, 3/2 - _n/2), Naturals0)`.
+/// None for an unknown pattern or an unbounded window.
+fn lattice_points(pattern: &str, lo: f64, hi: f64) -> Option<Vec<f64>> {
+    let image_set = Regex::new(r"^ImageSet\(Lambda\(_n, (.+)\), (Integers|Naturals0)\)$").ok()?;
+    let range = Regex::new(r"^Range\((.+), -?oo, (.+)\)$").ok()?;
+    let (offset, period, one_sided) = if let Some(caps) = image_set.captures(pattern) {
+        // The lambda is linear in _n: its value at 0 and its step to 1
+        let point = caps[1].replace("_n", "n").parse::<Expr>().ok()?.bind("n").ok()?;
+        let offset = point(0.0);
+        (offset, point(1.0) - offset, &caps[2] == "Naturals0")
+    } else {
+        let caps = range.captures(pattern)?;
+        (meval::eval_str(&caps[1]).ok()?, meval::eval_str(&caps[2]).ok()?, true)
+    };
+    if !lo.is_finite() || !hi.is_finite() || period == 0.0 {
+        return None;
+    }
+    let (a, b) = ((lo - offset) / period, (hi - offset) / period);
+    let (mut first, last) = (a.min(b).ceil() as i64, a.max(b).floor() as i64);
+    if one_sided {
+        first = first.max(0);
+    }
+    Some((first..=last).take(100_000).map(|k| offset + k as f64 * period).collect())
+}
+
+fn detect_domain(func_str: &str, func: &impl Fn(f64) -> f64, config: &GridConfig) -> Domain {
+    let func_lower = func_str.to_lowercase().replace(" ", "");
+    
+    // First, find any denominator zeros (singularities)
+    let denom_zeros = find_denominator_zeros(func_str, func, config);
+    
+    let tree = func_str.parse::<Expr>().ok().as_ref().and_then(build_tree);
+    let breakpoints = tree.as_ref().and_then(domain_breakpoints);
+    // An oscillating argument's sign pattern is only known on the scanned
+    // window, so its edge is not taken for an infinite end. One that never
+    // turned invalid over the window (sqrt(1 - sin(x)^2)) is taken as Reals.
+    let scan = || {
+        let scanned = scan_valid_domain(func, breakpoints.as_deref());
+        let scanned = if scanned != Domain::Reals && tree.as_ref().is_some_and(has_periodic_domain_argument) {
+            scanned.restrict(&Interval::new(-100.0, 100.0, false, false))
+        } else {
+            scanned
+        };
+        exclude_points(scanned, &denom_zeros)
+    };
+    
+    // Specific patterns
+    // sqrt(a - x^2) style
+    if func_lower.contains("sqrt") {
+        if func_lower == "sqrt(x)" {
+            return Domain::Interval(Interval::new(0.0, f64::INFINITY, false, true));
+        }
+        // General radicand: read the sign pattern, then drop any poles inside it
+        return scan();
+    }
+    
+    // log/ln functions
+    if (func_lower.contains("ln(") || func_lower.contains("log(")) && !func_lower.contains("abs")
+        && safe_eval(func, 0.5).is_some() && safe_eval(func, -0.5).is_none()
+    {
+        return Domain::Interval(Interval::new(0.0, f64::INFINITY, true, true));
+    }
+    
+    // x^x
+    if func_lower.contains("x^x") {
+        return Domain::Interval(Interval::new(0.0, f64::INFINITY, false, true));
+    }
+    
+    // Trig functions with periodic singularities, e.g. tan(x) or tan(x)^2, and
+    // gamma's poles at the nonpositive integers
+    if let Some(Some(pattern)) = tree.as_ref().and_then(pole_lattice_pattern) {
+        return Domain::PeriodicComplement { pattern };
+    }
+    
+    // (-1)^x and friends: a negative base is only real at whole exponents
+    if tree.as_ref().is_some_and(has_negative_base_power) {
+        return Domain::IntegerSet;
+    }
+    
+    // asin/acos arguments that can leave [-1, 1], and fractional powers (real
+    // only for a nonnegative base): read the valid region off a scan
+    if tree.as_ref().is_some_and(|t| has_unbounded_inverse_trig_arg(t) || has_fractional_power(t)) {
+        return scan();
+    }
+    
+    // If we found denominator zeros, create appropriate domain
+    if !denom_zeros.is_empty() {
+        let mut zeros = denom_zeros.clone();
+        zeros.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        
+        let mut intervals = Vec::new();
+        
+        // First interval: (-oo, first_zero)
+        intervals.push(Interval::new(f64::NEG_INFINITY, zeros[0], true, true));
+        
+        // Middle intervals
+        for i in 0..zeros.len() - 1 {
+            intervals.push(Interval::new(zeros[i], zeros[i + 1], true, true));
+        }
+        
+        // Last interval: (last_zero, oo)
+        intervals.push(Interval::new(zeros[zeros.len() - 1], f64::INFINITY, true, true));
+        
+        return Domain::UnionOfIntervals(intervals);
+    }
+    
+    Domain::Reals
+}
+
+/// Pole lattice of an expression whose only poles are those of tan, cot, sec
+/// or csc of x (or 1/sin(x), 1/cos(x)), or of gamma of a linear argument,
+/// wrapped in operations that add none of their own: sums, products, whole
+/// positive powers and functions defined everywhere. Some(None) when there are
+/// no poles at all; None when something else could introduce poles or two
+/// different lattices meet.
+fn pole_lattice_pattern(node: &Node) -> Option<Option<String>> {
+    const HALF_ODD: &str = "ImageSet(Lambda(_n, pi/2 + _n*pi), Integers)";
+    const WHOLE: &str = "ImageSet(Lambda(_n, _n*pi), Integers)";
+    let merge = |a: Option<String>, b: Option<String>| match (a, b) {
+        (Some(p), Some(q)) if p != q => None,
+        (a, b) => Some(a.or(b)),
+    };
+    let of_x = |args: &[Node]| matches!(args, [Node::Var(_)]);
+    match node {
+        Node::Num(_) | Node::Var(_) => Some(None),
+        Node::Neg(a) => pole_lattice_pattern(a),
+        Node::Add(l, r) | Node::Sub(l, r) | Node::Mul(l, r) => merge(pole_lattice_pattern(l)?, pole_lattice_pattern(r)?),
+        Node::Div(l, r) => {
+            // A whole power of the denominator has the same zeros
+            let base = match &**r {
+                Node::Pow(b, e) if matches!(**e, Node::Num(n) if n > 0.0 && n.fract() == 0.0) => &**b,
+                r => r,
+            };
+            let lattice = match base {
+                r if count_vars(r) == 0 => None,
+                Node::Func(name, args) if name == "sin" && of_x(args) => Some(WHOLE.to_string()),
+                Node::Func(name, args) if name == "cos" && of_x(args) => Some(HALF_ODD.to_string()),
+                _ => return None,
+            };
+            merge(pole_lattice_pattern(l)?, lattice)
+        }
+        Node::Pow(b, e) => match **e {
+            Node::Num(n) if n > 0.0 && n.fract() == 0.0 => pole_lattice_pattern(b),
+            _ => None,
+        },
+        Node::Func(name, args) => match name.as_str() {
+            "tan" | "sec" if of_x(args) => Some(Some(HALF_ODD.to_string())),
+            "cot" | "csc" if of_x(args) => Some(Some(WHOLE.to_string())),
+            // gamma(a*x + b) has poles where a*x + b = 0, -1, -2, ...
+            "gamma" if args.len() == 1 => {
+                let p = as_polynomial(&args[0])?;
+                let [b, a] = p[..] else { return None };
+                if a == 0.0 {
+                    return None;
+                }
+                let (start, step) = (-b / a, -1.0 / a);
+                if start.fract() == 0.0 && step.fract() == 0.0 {
+                    let end = if step < 0.0 { "-oo" } else { "oo" };
+                    return Some(Some(format!("Range({}, {}, {})", format_symbolic(start), end, format_symbolic(step))));
+                }
+                // Range only takes whole numbers; otherwise start + n*step over n >= 0
+                let sign = if step < 0.0 { "-" } else { "+" };
+                let multiple = match 1.0 / step.abs() {
+                    k if k.fract() == 0.0 => format!("_n/{}", k),
+                    _ => format!("_n*{}", format_symbolic(step.abs())),
+                };
+                let body = if start == 0.0 {
+                    format!("{}{}", if step < 0.0 { "-" } else { "" }, multiple)
+                } else {
+                    format!("{} {} {}", format_symbolic(start), sign, multiple)
+                };
+                Some(Some(format!("ImageSet(Lambda(_n, {}), Naturals0)", body)))
+            }
+            "sin" | "cos" | "exp" | "abs" | "sinh" | "cosh" | "tanh" | "atan" if args.len() == 1 => pole_lattice_pattern(&args[0]),
+            _ => None,
+        },
+    }
+}
+
+/// True when a constant negative base is raised to a power that depends on x
+fn has_negative_base_power(node: &Node) -> bool {
+    match node {
+        Node::Num(_) | Node::Var(_) => false,
+        Node::Pow(base, exp) if count_vars(exp) > 0 && count_vars(base) == 0 => {
+            as_polynomial(base).is_some_and(|p| p.len() == 1 && p[0] < 0.0) || has_negative_base_power(exp)
+        }
+        Node::Neg(a) => has_negative_base_power(a),
+        Node::Add(l, r) | Node::Sub(l, r) | Node::Mul(l, r) | Node::Div(l, r) | Node::Pow(l, r) => {
+            has_negative_base_power(l) || has_negative_base_power(r)
+        }
+        Node::Func(_, args) => args.iter().any(has_negative_base_power),
+    }
+}
+
+/// True when an x-dependent base is raised to a constant non-integer power
+fn has_fractional_power(node: &Node) -> bool {
+    match node {
+        Node::Num(_) | Node::Var(_) => false,
+        Node::Pow(base, exp) if count_vars(base) > 0 && count_vars(exp) == 0 => {
+            as_polynomial(exp).is_some_and(|p| p.len() == 1 && p[0].fract() != 0.0) || has_fractional_power(base)
+        }
+        Node::Neg(a) => has_fractional_power(a),
+        Node::Add(l, r) | Node::Sub(l, r) | Node::Mul(l, r) | Node::Div(l, r) | Node::Pow(l, r) => {
+            has_fractional_power(l) || has_fractional_power(r)
+        }
+        Node::Func(_, args) => args.iter().any(has_fractional_power),
+    }
+}
+
+/// True when some asin/acos argument isn't provably inside [-1, 1]
+fn has_unbounded_inverse_trig_arg(node: &Node) -> bool {
+    let unit = Interval { lo: -1.0, hi: 1.0, lo_open: false, hi_open: false };
+    match node {
+        Node::Num(_) | Node::Var(_) => false,
+        Node::Neg(a) => has_unbounded_inverse_trig_arg(a),
+        Node::Add(l, r) | Node::Sub(l, r) | Node::Mul(l, r) | Node::Div(l, r) | Node::Pow(l, r) => {
+            has_unbounded_inverse_trig_arg(l) || has_unbounded_inverse_trig_arg(r)
+        }
+        Node::Func(name, args) => {
+            let arg_escapes = matches!(name.as_str(), "asin" | "acos") && args.len() == 1 && {
+                let arg_range = composition_range(&args[0], &Interval::reals());
+                arg_range.and_then(|r| r.intersect(&unit)) != arg_range
+            };
+            arg_escapes || args.iter().any(has_unbounded_inverse_trig_arg)
+        }
+    }
+}
+
+/// Arguments whose sign or size decides where the function is defined: sqrt,
+/// ln and log arguments, fractional-power bases, and asin/acos arguments
+/// (shifted by -1 and +1, whose roots are where they leave [-1, 1])
+fn domain_arguments(node: &Node, out: &mut Vec<(Node, f64)>) {
+    match node {
+        Node::Num(_) | Node::Var(_) => {}
+        Node::Neg(a) => domain_arguments(a, out),
+        Node::Pow(base, exp) if count_vars(base) > 0 && count_vars(exp) == 0
+            && as_polynomial(exp).is_some_and(|p| p.len() == 1 && p[0].fract() != 0.0) =>
+        {
+            out.push(((**base).clone(), 0.0));
+            domain_arguments(base, out);
+        }
+        Node::Add(l, r) | Node::Sub(l, r) | Node::Mul(l, r) | Node::Div(l, r) | Node::Pow(l, r) => {
+            domain_arguments(l, out);
+            domain_arguments(r, out);
+        }
+        Node::Func(name, args) => {
+            if args.len() == 1 {
+                match name.as_str() {
+                    "sqrt" | "ln" | "log" => out.push((args[0].clone(), 0.0)),
+                    "asin" | "acos" => out.extend([(args[0].clone(), -1.0), (args[0].clone(), 1.0)]),
+                    _ => {}
+                }
+            }
+            args.iter().for_each(|a| domain_arguments(a, out));
+        }
+    }
+}
+
+/// Where the domain can change when every domain argument is a polynomial:
+/// the real roots of each (shifted) argument. None when some argument isn't a
+/// polynomial, so its sign pattern past the scan window is unknown.
+fn domain_breakpoints(node: &Node) -> Option<Vec<f64>> {
+    let mut args = Vec::new();
+    domain_arguments(node, &mut args);
+    let mut roots = Vec::new();
+    for (arg, shift) in &args {
+        let mut poly = as_polynomial(arg)?;
+        poly[0] += shift;
+        roots.extend(poly_real_roots(&poly));
+    }
+    Some(roots)
+}
+
+/// True when a domain argument oscillates (sqrt(sin(x))): its sign keeps
+/// changing past any finite scan, so the scanned domain is only a window of it
+fn has_periodic_domain_argument(node: &Node) -> bool {
+    fn periodic(node: &Node) -> bool {
+        match node {
+            Node::Num(_) | Node::Var(_) => false,
+            Node::Neg(a) => periodic(a),
+            Node::Add(l, r) | Node::Sub(l, r) | Node::Mul(l, r) | Node::Div(l, r) | Node::Pow(l, r) => periodic(l) || periodic(r),
+            Node::Func(name, args) => {
+                (matches!(name.as_str(), "sin" | "cos" | "tan" | "sec" | "csc" | "cot") && args.iter().any(|a| count_vars(a) > 0))
+                    || args.iter().any(periodic)
+            }
+        }
+    }
+    let mut args = Vec::new();
+    domain_arguments(node, &mut args);
+    args.iter().any(|(arg, _)| periodic(arg))
+}
+
+/// Domain read off the sign pattern of validity, densely on [-100, 100]. When
+/// the breakpoints are known (every domain argument a polynomial) the scan
+/// also samples at, beside and between them and past the outermost one, so
+/// boundaries far outside the window (sqrt(x - 200), sqrt((x-200)*(x-300)))
+/// are found; otherwise nothing past the window is assumed. Boundaries are
+/// bisected and kept closed when the function is defined exactly there; a
+/// valid outermost sample is an infinite end.
+fn scan_valid_domain(func: &impl Fn(f64) -> f64, breakpoints: Option<&[f64]>) -> Domain {
+    let (lo, hi, dense) = (-100.0, 100.0, 20000);
+    let mut xs: Vec<f64> = (0..=dense).map(|i| lo + (hi - lo) * (i as f64) / (dense as f64)).collect();
+    if let Some(roots) = breakpoints {
+        let mut roots: Vec<f64> = roots.iter().copied().filter(|r| r.is_finite()).collect();
+        roots.sort_by(f64::total_cmp);
+        for r in &roots {
+            let step = 1e-6 * r.abs().max(1.0);
+            xs.extend([r - step, *r, r + step]);
+        }
+        xs.extend(roots.windows(2).map(|w| 0.5 * (w[0] + w[1])));
+        if let (Some(first), Some(last)) = (roots.first(), roots.last()) {
+            xs.extend([first - 1.0 - first.abs(), last + 1.0 + last.abs()]);
+        }
+        xs.sort_by(f64::total_cmp);
+        xs.dedup();
+    }
+    let n = xs.len() - 1;
+    let valid: Vec<bool> = xs.iter().map(|&x| safe_eval(func, x).is_some()).collect();
+    
+    // Edge between a valid sample and an invalid one
+    let boundary = |mut inside: f64, mut outside: f64| {
+        for _ in 0..60 {
+            let mid = 0.5 * (inside + outside);
+            if safe_eval(func, mid).is_some() { inside = mid; } else { outside = mid; }
+        }
+        let edge = round_to_nice(inside);
+        (edge, safe_eval(func, edge).is_none())
+    };
+    
+    let mut parts = Vec::new();
+    let mut i = 0;
+    while i <= n {
+        if !valid[i] { i += 1; continue; }
+        let start = i;
+        while i < n && valid[i + 1] { i += 1; }
+        let (min, min_open) = if start == 0 { (f64::NEG_INFINITY, true) } else { boundary(xs[start], xs[start - 1]) };
+        let (max, max_open) = if i == n { (f64::INFINITY, true) } else { boundary(xs[i], xs[i + 1]) };
+        parts.push(Interval::new(min, max, min_open, max_open));
+        i += 1;
+    }
+    
+    match parts.len() {
+        0 => Domain::Empty,
+        1 if parts[0] == Interval::reals() => Domain::Reals,
+        1 => Domain::Interval(parts[0]),
+        _ => Domain::UnionOfIntervals(parts),
+    }
+}
+
+/// Remove isolated points (poles) from a domain, splitting the parts they fall inside
+fn exclude_points(domain: Domain, points: &[f64]) -> Domain {
+    let mut parts = match domain {
+        Domain::Reals => vec![Interval::reals()],
+        Domain::Interval(part) => vec![part],
+        Domain::UnionOfIntervals(parts) => parts,
+        other => return other,
+    };
+    for &z in points {
+        parts = parts.into_iter()
+            .flat_map(|part| {
+                if z > part.lo && z < part.hi {
+                    vec![Interval { hi: z, hi_open: true, ..part }, Interval { lo: z, lo_open: true, ..part }]
+                } else if z == part.lo || z == part.hi {
+                    vec![Interval { lo_open: part.lo_open || z == part.lo, hi_open: part.hi_open || z == part.hi, ..part }]
+                } else {
+                    vec![part]
+                }
+            })
+            .collect();
+    }
+    match parts.len() {
+        1 if parts[0] == Interval::reals() => Domain::Reals,
+        1 => Domain::Interval(parts[0]),
+        _ => Domain::UnionOfIntervals(parts),
+    }
+}
+
+// =============================================================================
+// GRID GENERATION
+// =============================================================================
+
+/// True when a periodic function has a variable denominator inside its argument,
+/// e.g. sin(1/x): it oscillates infinitely fast toward the denominator zeros.
+fn has_reciprocal_oscillation(node: &Node) -> bool {
+    fn has_var_denominator(node: &Node) -> bool {
+        match node {
+            Node::Num(_) | Node::Var(_) => false,
+            Node::Neg(a) => has_var_denominator(a),
+            Node::Div(l, r) => count_vars(r) > 0 || has_var_denominator(l),
+            Node::Pow(b, e) => {
+                matches!(**e, Node::Num(n) if n < 0.0) && count_vars(b) > 0
+                    || has_var_denominator(b) || has_var_denominator(e)
+            }
+            Node::Add(l, r) | Node::Sub(l, r) | Node::Mul(l, r) => {
+                has_var_denominator(l) || has_var_denominator(r)
+            }
+            Node::Func(_, args) => args.iter().any(has_var_denominator),
+        }
+    }
+    match node {
+        Node::Num(_) | Node::Var(_) => false,
+        Node::Neg(a) => has_reciprocal_oscillation(a),
+        Node::Add(l, r) | Node::Sub(l, r) | Node::Mul(l, r) | Node::Div(l, r) | Node::Pow(l, r) => {
+            has_reciprocal_oscillation(l) || has_reciprocal_oscillation(r)
+        }
+        Node::Func(name, args) => {
+            (matches!(name.as_str(), "sin" | "cos" | "tan") && args.iter().any(has_var_denominator))
+                || args.iter().any(has_reciprocal_oscillation)
+        }
+    }
+}
+
+/// Parallel evaluation; each thread binds its own copy of the parsed expression.
+/// Only points with a finite value are kept.
+fn sample_grid(expr: &Expr, grid: &[f64]) -> Vec<(f64, f64)> {
+    map_init(
+        grid,
+        || bind_x(expr.clone()).ok(),
+        |f, &x| {
+            let val = f.as_ref()?(x);
+            if is_valid(val) { Some((x, val)) } else { None }
+        },
+    )
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Spacing of the full-line scan over [-100, 100], and of the coarse scan used
+/// when nothing on the line calls for the fine one
+const FINE_SCAN_STEP: f64 = 0.005;
+const COARSE_SCAN_STEP: f64 = 0.05;
+
+/// Pole-free functions on all of R start from the coarse scan; `densify_grid`
+/// fills in around whatever features that scan turns up
+fn uses_coarse_grid(domain: &Domain, denom_zeros: &[f64], oscillation_centers: &[f64]) -> bool {
+    matches!(domain, Domain::Reals) && denom_zeros.is_empty() && oscillation_centers.is_empty()
+}
+
+/// Fine-step points around the coarse samples where something happens: a
+/// turning point in the values, or a jump well beyond the neighbouring slopes
+fn densify_grid(samples: &[(f64, f64)]) -> Vec<f64> {
+    let per_step = (COARSE_SCAN_STEP / FINE_SCAN_STEP) as usize;
+    let mut points = Vec::new();
+    for w in samples.windows(3) {
+        let [(x0, y0), (x1, y1), (x2, y2)] = [w[0], w[1], w[2]];
+        if x2 - x0 > 2.5 * COARSE_SCAN_STEP {
+            continue;
+        }
+        let (d0, d1) = (y1 - y0, y2 - y1);
+        let turning = d0 * d1 < 0.0 || (d0 == 0.0) != (d1 == 0.0);
+        let jump = d1.abs() > 10.0 * d0.abs() && d1.abs() > 1e-9;
+        if turning || jump {
+            points.extend((1..2 * per_step).map(|k| x0 + k as f64 * FINE_SCAN_STEP).filter(|&x| x != x1));
+        }
+    }
+    points.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    points.dedup_by(|a, b| (*a - *b).abs() < 1e-12);
+    points
+}
+
+/// Samples over the grid, plus the fine points a coarse grid's features call
+/// for; those are appended to `grid` so its length counts every point tried
+fn sample_smart_grid(expr: &Expr, grid: &mut Vec<f64>, coarse: bool) -> Vec<(f64, f64)> {
+    let mut samples = sample_grid(expr, grid);
+    if coarse {
+        let extra = densify_grid(&samples);
+        samples.extend(sample_grid(expr, &extra));
+        samples.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        grid.extend(extra);
+    }
+    samples
+}
+
+fn generate_smart_grid(domain: &Domain, denom_zeros: &[f64], oscillation_centers: &[f64]) -> Vec<f64> {
+    smart_grid_points(domain, denom_zeros, oscillation_centers, uses_coarse_grid(domain, denom_zeros, oscillation_centers)).collect()
+}
+
+/// Grid point ordered by `total_cmp`, so it can sit in a heap
+#[derive(Debug, Clone, Copy)]
+struct GridKey(f64);
+
+impl PartialEq for GridKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for GridKey {}
+
+impl PartialOrd for GridKey {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for GridKey {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+/// The smart grid's points in increasing order, generated lazily: a k-way merge
+/// of sorted sub-grids (the uniform scan, approaches to poles and trig landmarks,
+/// the wide scan), so a dense scan never holds every point at once. Points
+/// within 1e-12 of the previous one are skipped.
+struct SmartGrid {
+    sources: Vec<Box<dyn Iterator<Item = f64>>>,
+    heads: BinaryHeap<Reverse<(GridKey, usize)>>,
+    last: Option<f64>,
+}
+
+impl SmartGrid {
+    fn new(sources: Vec<Box<dyn Iterator<Item = f64>>>) -> Self {
+        let mut grid = SmartGrid { sources, heads: BinaryHeap::new(), last: None };
+        for i in 0..grid.sources.len() {
+            grid.advance(i);
+        }
+        grid
+    }
+
+    fn advance(&mut self, source: usize) {
+        if let Some(x) = self.sources[source].next() {
+            self.heads.push(Reverse((GridKey(x), source)));
+        }
+    }
+}
+
+impl Iterator for SmartGrid {
+    type Item = f64;
+
+    fn next(&mut self) -> Option<f64> {
+        loop {
+            let Reverse((GridKey(x), source)) = self.heads.pop()?;
+            self.advance(source);
+            if self.last.is_some_and(|last| (x - last).abs() < 1e-12) {
+                continue;
+            }
+            self.last = Some(x);
+            return Some(x);
+        }
+    }
+}
+
+/// Points at 10^-k on either side of `centre` for each k in `ks`, in increasing order
+fn approach(centre: f64, ks: std::ops::RangeInclusive<i32>) -> impl Iterator<Item = f64> {
+    let below = ks.clone().map(move |k| centre - 10.0_f64.powi(-k));
+    let above = ks.rev().map(move |k| centre + 10.0_f64.powi(-k));
+    below.chain(above)
+}
+
+/// `lo`, `lo + step`, ... up to `hi`, accumulated the way a scan loop would
+fn uniform_scan(lo: f64, hi: f64, step: f64) -> impl Iterator<Item = f64> {
+    std::iter::successors(Some(lo), move |&x| Some(x + step)).take_while(move |&x| x <= hi)
+}
+
+fn smart_grid_points(domain: &Domain, denom_zeros: &[f64], oscillation_centers: &[f64], coarse: bool) -> SmartGrid {
+    let mut sources: Vec<Box<dyn Iterator<Item = f64>>> = Vec::new();
+    
+    match *domain {
+        Domain::Interval(Interval { lo: min, hi: max, lo_open: min_open, hi_open: max_open }) => {
+            // Closed finite endpoints belong to the domain and are sampled exactly
+            let ends = [(!min_open && min.is_finite()).then_some(min), (!max_open && max.is_finite()).then_some(max)];
+            sources.push(Box::new(ends.into_iter().flatten()));
+            let lo = if min == f64::NEG_INFINITY { -1000.0 } else { min + 1e-8 };
+            let hi = if max == f64::INFINITY { 1000.0 } else { max - 1e-8 };
+            sources.push(Box::new(uniform_scan(lo, hi, (hi - lo) / 20000.0)));
+            
+            // Extra points near boundaries
+            sources.push(Box::new((1..=10).rev().map(move |k| lo + 10.0_f64.powi(-k)).filter(move |&x| x <= hi)));
+            sources.push(Box::new((1..=10).map(move |k| hi - 10.0_f64.powi(-k)).filter(move |&x| x >= lo)));
+        }
+        Domain::IntegerSet => {
+            sources.push(Box::new((-100..=100).map(f64::from)));
+        }
+        _ => {
+            // Dense scan avoiding singularities
+            let step = if coarse { COARSE_SCAN_STEP } else { FINE_SCAN_STEP };
+            let zeros = denom_zeros.to_vec();
+            sources.push(Box::new(uniform_scan(-100.0, 100.0, step).filter(move |&x| !zeros.iter().any(|&z| (x - z).abs() < 0.001))));
+            
+            // Points near singularities (but not at them)
+            for &z in denom_zeros {
+                sources.push(Box::new(approach(z, 3..=10)));
+            }
+            
+            // Near pi multiples for trig
+            for n in -20..=20 {
+                sources.push(Box::new(approach(n as f64 * PI, 3..=7)));
+                sources.push(Box::new(approach(n as f64 * PI / 2.0, 3..=7)));
+            }
+            
+            // Wide scan
+            let wide: Vec<f64> = std::iter::successors(Some(100.0_f64), |&x| Some(x * 1.5)).take_while(|&x| x < 1e6).collect();
+            sources.push(Box::new(wide.clone().into_iter().rev().map(|x| -x)));
+            sources.push(Box::new(wide.into_iter()));
+        }
+    }
+    
+    // Closed finite ends of a union's pieces belong to the domain too
+    if let Domain::UnionOfIntervals(parts) = domain {
+        let mut ends: Vec<f64> = parts.iter()
+            .flat_map(|part| [(!part.lo_open && part.lo.is_finite()).then_some(part.lo), (!part.hi_open && part.hi.is_finite()).then_some(part.hi)])
+            .flatten()
+            .collect();
+        ends.sort_by(f64::total_cmp);
+        sources.push(Box::new(ends.into_iter()));
+    }
+    
+    // Log-spaced samples toward points of unbounded oscillation
+    for &c in oscillation_centers {
+        let n = 20000;
+        let d = move |i: i32| 10.0_f64.powf(-6.0 + 7.0 * (i as f64) / (n as f64));
+        sources.push(Box::new((0..=n).rev().map(move |i| c - d(i))));
+        sources.push(Box::new((0..=n).map(move |i| c + d(i))));
+    }
+    
+    SmartGrid::new(sources)
+}
+
+// =============================================================================
+// CRITICAL POINTS
+// =============================================================================
+fn find_critical_points(func_str: &str, domain: &Domain, config: &SolverConfig) -> Vec<f64> {
+    let (lo, hi) = match domain {
+        Domain::Interval(Interval { lo: min, hi: max, .. }) => {
+            (if *min == f64::NEG_INFINITY { -1000.0 } else { *min + 1e-6 },
+             if *max == f64::INFINITY { 1000.0 } else { *max - 1e-6 })
+        }
+        _ => (-1000.0, 1000.0),
+    };
+    
+    let n_samples = 10000;
+    let step = (hi - lo) / (n_samples as f64);
+    let samples: Vec<f64> = (0..=n_samples).map(|i| lo + (i as f64) * step).collect();
+    
+    let parsed: Option<Expr> = func_str.parse().ok();
+    let derivs: Vec<Option<f64>> = map_init(
+        &samples,
+        || parsed.clone().and_then(|e| bind_x(e).ok()),
+        |func, &x| {
+            let func = func.as_ref()?;
+            let h = config.derivative_h * (1.0 + x.abs());
+            let f_plus = func(x + h);
+            let f_minus = func(x - h);
+            if is_valid(f_plus) && is_valid(f_minus) {
+                let d = (f_plus - f_minus) / (2.0 * h);
+                if is_valid(d) { Some(d) } else { None }
+            } else {
+                None
+            }
+        },
+    );
+    
+    let mut critical_points = Vec::new();
+    for i in 0..derivs.len() - 1 {
+        if let (Some(d1), Some(d2)) = (derivs[i], derivs[i + 1]) {
+            if d1 * d2 < 0.0 {
+                critical_points.push((samples[i] + samples[i + 1]) / 2.0);
+            }
+        }
+    }
+    
+    critical_points
+}
+
+// =============================================================================
+// EXACT POLYNOMIAL RANGES - Rational arithmetic for classroom polynomials
+// =============================================================================
+
+/// Range of a polynomial with its finite ends as exact rationals. `None` is an
+/// infinite end; the finite ends of a polynomial's range are always attained.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RationalRange {
+    pub lo: Option<Ratio<i64>>,
+    pub hi: Option<Ratio<i64>>,
+}
+
+impl RationalRange {
+    /// The same range in floating point
+    pub fn to_range(&self) -> Range {
+        let to_f64 = |r: &Ratio<i64>| *r.numer() as f64 / *r.denom() as f64;
+        let lo = self.lo.as_ref().map_or(f64::NEG_INFINITY, to_f64);
+        let hi = self.hi.as_ref().map_or(f64::INFINITY, to_f64);
+        let range_type = if lo == hi { RangeType::FiniteSet(vec![lo]) } else { RangeType::Simple };
+        Range { hull: Interval::new(lo, hi, self.lo.is_none(), self.hi.is_none()), range_type }
+    }
+}
+
+impl std::fmt::Display for RationalRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match (&self.lo, &self.hi) {
+            (None, None) => write!(f, "Reals"),
+            (Some(lo), Some(hi)) if lo == hi => write!(f, "FiniteSet({})", lo),
+            (Some(lo), Some(hi)) => write!(f, "Interval({}, {})", lo, hi),
+            (Some(lo), None) => write!(f, "Interval.Ropen({}, oo)", lo),
+            (None, Some(hi)) => write!(f, "Interval.Lopen(-oo, {})", hi),
+        }
+    }
+}
+
+/// Exact range of a polynomial whose coefficients and extreme point are
+/// rational, e.g. `x^2 - x` -> Interval.Ropen(-1/4, oo). None for anything
+/// else: a non-polynomial, an irrational coefficient or turning point (x^4 - x^2
+/// bottoms out at +-1/sqrt(2)), or numbers too large for i64 arithmetic.
+pub fn solve_exact_rational(func_str: &str) -> Option<RationalRange> {
+    let expr: Expr = canonical_variable(func_str)?.parse().ok()?;
+    let floats = as_polynomial(&build_tree(&expr)?)?;
+    let coeffs: Vec<Ratio<i64>> = floats.iter().map(|&c| float_to_ratio(c, 1000)).collect::<Option<_>>()?;
+    let degree = coeffs.len() - 1;
+    if degree == 0 {
+        return Some(RationalRange { lo: Some(coeffs[0]), hi: Some(coeffs[0]) });
+    }
+    if degree % 2 == 1 {
+        return Some(RationalRange { lo: None, hi: None });
+    }
+    
+    // Even degree: the closed end is the best value over the turning points
+    let opens_up = coeffs[degree] > Ratio::from_integer(0);
+    let slope: Vec<Ratio<i64>> = (1..=degree)
+        .map(|i| coeffs[i].checked_mul(&Ratio::from_integer(i as i64)))
+        .collect::<Option<_>>()?;
+    let values = rational_roots(&slope)?.iter()
+        .map(|x| eval_exact(&coeffs, x))
+        .collect::<Option<Vec<_>>>()?;
+    let best = if opens_up { values.into_iter().min()? } else { values.into_iter().max()? };
+    
+    // A turning point the rational root test can't see would go further than
+    // `best`; a scan out to the Cauchy bound of the slope's roots rules that out
+    let best_f = *best.numer() as f64 / *best.denom() as f64;
+    let lead = floats[degree] * degree as f64;
+    let bound = 1.0 + (1..degree).map(|i| (floats[i] * i as f64 / lead).abs()).fold(0.0, f64::max);
+    let beaten = (0..=20_000).map(|i| -bound + 2.0 * bound * i as f64 / 20_000.0).any(|x| {
+        let y = floats.iter().rev().fold(0.0, |acc, c| acc * x + c);
+        let slack = 1e-9 * best_f.abs().max(1.0);
+        if opens_up { y < best_f - slack } else { y > best_f + slack }
+    });
+    if beaten {
+        return None;
+    }
+    
+    Some(if opens_up {
+        RationalRange { lo: Some(best), hi: None }
+    } else {
+        RationalRange { lo: None, hi: Some(best) }
+    })
+}
+
+/// Horner evaluation in rationals, None on i64 overflow
+fn eval_exact(coeffs: &[Ratio<i64>], x: &Ratio<i64>) -> Option<Ratio<i64>> {
+    coeffs.iter().rev().try_fold(Ratio::from_integer(0), |acc, c| acc.checked_mul(x)?.checked_add(c))
+}
+
+/// Rational roots of a polynomial (lowest degree first) by the rational root
+/// test: every one is p/q with p dividing the constant term and q the leading
+/// one, once the coefficients are cleared to integers. None when the integer
+/// ends are too large to enumerate divisors of, or arithmetic overflows.
+fn rational_roots(poly: &[Ratio<i64>]) -> Option<Vec<Ratio<i64>>> {
+    fn gcd(a: i64, b: i64) -> i64 {
+        if b == 0 { a.abs() } else { gcd(b, a % b) }
+    }
+    fn divisors(n: i64) -> Vec<i64> {
+        (1..).take_while(|d| d * d <= n).filter(|d| n % d == 0).flat_map(|d| [d, n / d]).collect()
+    }
+    
+    let lcm = poly.iter().try_fold(1_i64, |acc, c| (acc / gcd(acc, *c.denom())).checked_mul(*c.denom()))?;
+    let ints: Vec<i64> = poly.iter()
+        .map(|c| c.checked_mul(&Ratio::from_integer(lcm)).map(|c| c.to_integer()))
+        .collect::<Option<_>>()?;
+    
+    // x = 0 divides out of a polynomial with no constant term
+    let zeros = ints.iter().take_while(|&&c| c == 0).count();
+    let ints = &ints[zeros.min(ints.len() - 1)..];
+    let mut roots = if zeros > 0 { vec![Ratio::from_integer(0)] } else { Vec::new() };
+    let (first, last) = (ints[0].abs(), ints[ints.len() - 1].abs());
+    if ints.len() == 1 || first > 1_000_000 || last > 1_000_000 {
+        return (ints.len() == 1).then_some(roots);
+    }
+    
+    let exact: Vec<Ratio<i64>> = ints.iter().map(|&c| Ratio::from_integer(c)).collect();
+    for p in divisors(first) {
+        for q in divisors(last) {
+            for candidate in [Ratio::new(p, q), Ratio::new(-p, q)] {
+                if !roots.contains(&candidate) && eval_exact(&exact, &candidate)? == Ratio::from_integer(0) {
+                    roots.push(candidate);
+                }
+            }
+        }
+    }
+    Some(roots)
+}
+
+// =============================================================================
+// MAIN SOLVER
+// =============================================================================
+
+/// Method reported when a deadline cut the analysis short
+pub const TIMEOUT_METHOD: &str = "TIMEOUT (partial)";
+
+pub fn solve(func_str: &str) -> Option<SolveResult> {
+    solve_until(func_str, None, None, &GridConfig::default())
+}
+
+/// Like `solve`, with custom pole-scan settings
+pub fn solve_with_config(func_str: &str, config: &GridConfig) -> Option<SolveResult> {
+    solve_until(func_str, None, None, config)
+}
+
+/// Like `solve`, but only over the part of the natural domain inside `window`
+/// (an interval; other domain shapes are replaced by their hull).
+pub fn solve_on_domain(func_str: &str, window: &Domain) -> Option<SolveResult> {
+    let restriction = match window {
+        Domain::Reals => None,
+        Domain::Interval(part) => Some(*part),
+        other => {
+            let (lo, hi) = other.hull();
+            Some(Interval::new(lo, hi, true, true))
+        }
+    };
+    solve_until(func_str, restriction, None, &GridConfig::default())
+}
+
+/// Like `solve`, with the named constants fixed, e.g. `a*x^2` with `a = 2`.
+/// The one name left unbound is the variable. None for a non-finite value or
+/// a name that would rebind the solve variable x or a built-in constant (pi, e).
+pub fn solve_with_params(func_str: &str, params: &[(&str, f64)]) -> Option<SolveResult> {
+    let builtins = Context::new();
+    let mut ctx = Context::empty();
+    for &(name, value) in params {
+        if !value.is_finite() || name == "x" || builtins.get_var(name).is_some() {
+            return None;
+        }
+        ctx.var(name, value);
+    }
+    // Bound names become their values token by token, so a name inside another
+    // name or a number is never touched
+    let bound: String = meval::tokenizer::tokenize(func_str).ok()?.into_iter()
+        .map(|token| match token {
+            Token::Var(name) => ctx.get_var(&name).map_or(name, |value| format!("({})", value)),
+            Token::Number(n) => format!("{}", n),
+            Token::Binary(op) | Token::Unary(op) => match op {
+                Operation::Plus => "+",
+                Operation::Minus => "-",
+                Operation::Times => "*",
+                Operation::Div => "/",
+                Operation::Rem => "%",
+                Operation::Pow => "^",
+            }.to_string(),
+            Token::LParen => "(".to_string(),
+            Token::RParen => ")".to_string(),
+            Token::Comma => ", ".to_string(),
+            Token::Func(name, _) => format!("{}(", name),
+        })
+        .collect();
+    solve(&bound)
+}
+
+/// Like `solve`, but gives up once `timeout` has elapsed. The deadline is checked
+/// between stages; when it passes, the bounds sampled so far are returned with
+/// method `TIMEOUT_METHOD`.
+pub fn solve_with_deadline(func_str: &str, timeout: Duration) -> Option<SolveResult> {
+    solve_until(func_str, None, Some(Instant::now() + timeout), &GridConfig::default())
+}
+
+/// Like `solve`, as the `Display` block with domain and range written by
+/// `symbols` instead of the built-in constants
+pub fn solve_formatted(func_str: &str, symbols: &dyn SymbolFormatter) -> Option<String> {
+    solve(func_str).map(|result| result.fmt_with(symbols))
+}
+
+/// Like `solve`, memoized for the life of the process under the expression's
+/// `canonicalize` key, so reordered spellings of one function are solved once
+pub fn solve_cached(func_str: &str) -> Option<SolveResult> {
+    static CACHE: OnceLock<Mutex<HashMap<String, SolveResult>>> = OnceLock::new();
+    let key = canonicalize(func_str)?;
+    let cache = CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some(hit) = cache.lock().ok()?.get(&key) {
+        return Some(hit.clone());
+    }
+    let result = solve(func_str)?;
+    cache.lock().ok()?.insert(key, result.clone());
+    Some(result)
+}
+
+/// Method reported by `solve_preview`
+pub const PREVIEW_METHOD: &str = "Preview (approximate)";
+
+/// Rough answer for live typing, to be replaced by `solve` once the input
+/// settles. The domain is detected as usual. The range comes from about 2000
+/// samples, a 5-window Brent sweep and the limits at the ends and poles. It
+/// skips the achievability and gap checks, so the range is one interval with
+/// its finite ends shown closed.
+pub fn solve_preview(func_str: &str) -> Option<SolveResult> {
+    let config = GridConfig::default();
+    let func_str = &rewrite_odd_roots(&canonical_variable(func_str)?);
+    let func_str = &rewrite_hyperbolic_pairs(func_str).unwrap_or_else(|| func_str.clone());
+    let expr: Expr = func_str.parse().ok()?;
+    let tree = build_tree(&expr);
+    let func = bind_x(expr).ok()?;
+    let denom_zeros = find_denominator_zeros(func_str, &func, &config);
+    let domain = detect_domain(func_str, &func, &config);
+    
+    // 1001 even points over [-10, 10], then 500 log-spaced per side out to 1e6
+    let near = (0..=1000).map(|i| -10.0 + 0.02 * i as f64);
+    let far = (1..=500).map(|i| 10.0 * 1e5_f64.powf(i as f64 / 500.0)).flat_map(|x| [-x, x]);
+    let values: Vec<f64> = near.chain(far)
+        .filter(|&x| domain.contains(x))
+        .filter_map(|x| safe_eval(&func, x))
+        .collect();
+    if values.is_empty() {
+        return Some(SolveResult {
+            domain,
+            range: Range { hull: Interval::new(0.0, 0.0, true, true), range_type: RangeType::Simple },
+            method: "Undefined".to_string(),
+            warnings: vec!["no finite values at any preview point".to_string()],
+        });
+    }
+    let mut lo = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let mut hi = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    
+    let (domain_lo, domain_hi) = domain.hull();
+    if let Some((sweep_min, sweep_max)) = brent_sweep(func_str, &domain, domain_lo.max(-100.0), domain_hi.min(100.0), 5, &config.solver) {
+        lo = lo.min(sweep_min);
+        hi = hi.max(sweep_max);
+    }
+    
+    // Unbounded toward an infinite end, either side of a pole or an open end
+    let mut divergences: Vec<f64> = [domain_lo, domain_hi].into_iter()
+        .filter(|end| end.is_infinite())
+        .filter_map(|end| analyze_limit(&func, end, &config.solver))
+        .collect();
+    let open_ends: Vec<f64> = match &domain {
+        Domain::Interval(part) => [(part.lo, part.lo_open), (part.hi, part.hi_open)].into_iter()
+            .filter(|&(end, open)| open && end.is_finite())
+            .map(|(end, _)| end)
+            .collect(),
+        _ => Vec::new(),
+    };
+    for &z in denom_zeros.iter().chain(&open_ends) {
+        divergences.extend([-1.0, 1.0].into_iter().filter_map(|side| pole_divergence(&func, z, side)));
+    }
+    if hi > config.solver.inf_threshold || divergences.contains(&f64::INFINITY) { hi = f64::INFINITY; }
+    if lo < -config.solver.inf_threshold || divergences.contains(&f64::NEG_INFINITY) { lo = f64::NEG_INFINITY; }
+    
+    // A single occurrence of x still gets its exact range; it costs nothing
+    let nice = |v: f64| if v.is_finite() { round_to_nice(v) } else { v };
+    let hull = tree.as_ref()
+        .filter(|t| count_vars(t) == 1)
+        .and_then(|t| composition_range(t, &Interval::reals()))
+        .filter(|span| !span.lo.is_nan() && !span.hi.is_nan())
+        .unwrap_or_else(|| Interval::new(nice(lo), nice(hi), lo.is_infinite(), hi.is_infinite()));
+    Some(SolveResult {
+        domain,
+        range: Range { hull, range_type: RangeType::Simple },
+        method: PREVIEW_METHOD.to_string(),
+        warnings: Vec::new(),
+    })
+}
+
+/// Best-effort result from the samples gathered before the deadline
+fn partial_result(domain: Domain, rough_min: f64, rough_max: f64) -> SolveResult {
+    SolveResult {
+        domain,
+        range: Range { hull: Interval::new(rough_min, rough_max, false, false), range_type: RangeType::Simple },
+        method: TIMEOUT_METHOD.to_string(),
+        warnings: Vec::new(),
+    }
+}
+
+fn solve_until(func_str: &str, restriction: Option<Interval>, deadline: Option<Instant>, config: &GridConfig) -> Option<SolveResult> {
+    let expired = || deadline.is_some_and(|d| Instant::now() >= d);
+    let func_str = &rewrite_odd_roots(&canonical_variable(func_str)?);
+    let func_str = &rewrite_hyperbolic_pairs(func_str).unwrap_or_else(|| func_str.clone());
+    let expr: Expr = func_str.parse().ok()?;
+    let tree = build_tree(&expr);
+    let raw_func = bind_x(expr.clone()).ok()?;
+    
+    // Find denominator zeros first
+    let mut denom_zeros = find_denominator_zeros(func_str, &raw_func, config);
+    
+    // Detect domain
+    let mut domain = detect_domain(func_str, &raw_func, config);
+    
+    // A restricted window masks everything outside it, so the sampling, limit
+    // and achievability helpers below only see the window
+    if let Some(window) = &restriction {
+        domain = domain.restrict(window);
+        denom_zeros.retain(|&z| window.lo <= z && z <= window.hi);
+    }
+    let func = |x: f64| if restriction.is_none() || domain.contains(x) { raw_func(x) } else { f64::NAN };
+    
+    // An oscillating sqrt/ln argument keeps switching sign past the scanned
+    // window, so neither the domain nor the range can be called exact
+    let window_domain = tree.as_ref().is_some_and(has_periodic_domain_argument)
+        && matches!(domain, Domain::Interval(_) | Domain::UnionOfIntervals(_))
+        && restriction.is_none_or(|w| w.lo < -100.0 || w.hi > 100.0);
+    
+    // Generate evaluation grid, densified where sin(1/x)-style terms oscillate
+    let oscillation_centers: &[f64] = match &tree {
+        Some(t) if has_reciprocal_oscillation(t) => &denom_zeros,
+        _ => &[],
+    };
+    let mut grid = generate_smart_grid(&domain, &denom_zeros, oscillation_centers);
+    if restriction.is_some() {
+        grid.retain(|&x| domain.contains(x));
+    }
+    
+    let samples = sample_smart_grid(&expr, &mut grid, uses_coarse_grid(&domain, &denom_zeros, oscillation_centers));
+    let values: Vec<f64> = samples.iter().map(|&(_, y)| y).collect();
+    
+    // An empty domain has an empty range, whatever stray samples came back
+    if values.is_empty() || domain == Domain::Empty {
+        let warning = if values.is_empty() {
+            format!("no finite values at any of {} grid points", grid.len())
+        } else {
+            format!("{} of {} grid points gave finite values outside the detected domain", values.len(), grid.len())
+        };
+        return Some(SolveResult {
+            domain,
+            range: Range { hull: Interval::new(0.0, 0.0, true, true), range_type: RangeType::Simple },
+            method: "Undefined".to_string(),
+            warnings: vec![warning],
+        });
+    }
+    
+    // Closed ends of the domain's pieces are sampled exactly; they say nothing
+    // about how much of the scan is defined, so they stay out of the sparse ratio
+    let closed_ends: Vec<f64> = domain.as_intervals().unwrap_or_default().iter()
+        .flat_map(|part| [(!part.lo_open).then_some(part.lo), (!part.hi_open).then_some(part.hi)])
+        .flatten()
+        .filter(|x| x.is_finite())
+        .collect();
+    let is_end = |x: &f64| closed_ends.binary_search_by(|end| end.total_cmp(x)).is_ok();
+    let scanned = grid.iter().filter(|x| !is_end(x)).count();
+    let valid = samples.iter().filter(|(x, _)| !is_end(x)).count();
+    let mut warnings: Vec<String> = Vec::new();
+    if window_domain {
+        warnings.push("domain argument oscillates; domain and range only resolved on [-100, 100]".to_string());
+    }
+    let sparse = (valid as f64) < MIN_VALID_FRACTION * scanned as f64;
+    if sparse {
+        warnings.push(format!("only {} of {} grid points gave finite values; extrema between them may be missed",
+            valid, scanned));
+    }
+    
+    let mut rough_min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let mut rough_max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    
+    // Find critical points
+    if expired() {
+        return Some(partial_result(domain, rough_min, rough_max));
+    }
+    let critical_points = find_critical_points(func_str, &domain, &config.solver);
+    for &cp in critical_points.iter().chain(&closed_ends) {
+        if let Some(val) = safe_eval(&func, cp) {
+            rough_min = rough_min.min(val);
+            rough_max = rough_max.max(val);
+        }
+    }
+    
+    // Brent optimization
+    let (search_lo, search_hi) = match &domain {
+        Domain::Interval(Interval { lo: min, hi: max, .. }) => {
+            (if *min == f64::NEG_INFINITY { -100.0 } else { *min + 1e-8 },
+             if *max == f64::INFINITY { 100.0 } else { *max - 1e-8 })
+        }
+        _ => (-100.0, 100.0),
+    };
+    
+    if expired() {
+        return Some(partial_result(domain, rough_min, rough_max));
+    }
+    let sweep_domain = if restriction.is_some() { &domain } else { &Domain::Reals };
+    if let Some((sweep_min, sweep_max)) = brent_sweep(func_str, sweep_domain, search_lo, search_hi, 20, &config.solver) {
+        rough_min = rough_min.min(sweep_min);
+        rough_max = rough_max.max(sweep_max);
+    }
+    
+    // Analyze limits
+    let mut has_inf_pos = rough_max > config.solver.inf_threshold;
+    let mut has_inf_neg = rough_min < -config.solver.inf_threshold;
+    
+    // Rational functions: end behavior follows exactly from the degrees
+    let rational = tree.as_ref().and_then(rational_parts);
+    let end_limit = |toward: f64| match &rational {
+        Some((p, q)) => Some(rational_limit(p, q, toward)),
+        None => analyze_limit(&func, toward, &config.solver),
+    };
+    
+    let (domain_lo, domain_hi) = domain.hull();
+    let mut unclassified_ends = Vec::new();
+    // Outermost sample and end limit for each infinite end with a limit
+    let mut end_tails: Vec<(f64, f64)> = Vec::new();
+    for end in [domain_hi, domain_lo] {
+        if end.is_finite() { continue; }
+        match end_limit(end) {
+            Some(lim) => {
+                if lim == f64::INFINITY { has_inf_pos = true; }
+                if lim == f64::NEG_INFINITY { has_inf_neg = true; }
+                let outermost = if end > 0.0 { samples.last() } else { samples.first() };
+                if let Some(&(_, y)) = outermost {
+                    end_tails.push((lim, y));
+                }
+            }
+            None => {
+                // No limit: unbounded if the oscillation's envelope keeps climbing
+                let (grows_up, grows_down) = envelope_growth(&func, end);
+                if grows_up { has_inf_pos = true; }
+                if grows_down { has_inf_neg = true; }
+                if !(grows_up || grows_down) { unclassified_ends.push(end); }
+            }
+        }
+    }
+    
+
+    // An extreme on the outermost sample may lie beyond the scan (x*exp(-x/10^6)
+    // peaks at 1e6): widen once past that edge, up to a thousandfold
+    let mut clipped_edges = Vec::new();
+    let edges = [(samples.last(), domain_hi), (samples.first(), domain_lo)];
+    for (edge, end) in edges {
+        let Some(&(x_edge, y_edge)) = edge else { continue };
+        let at_extreme = (y_edge == rough_max && !has_inf_pos) || (y_edge == rough_min && !has_inf_neg);
+        if end.is_finite() || !at_extreme || x_edge == 0.0 || end_limit(end).is_some_and(f64::is_infinite) {
+            continue;
+        }
+        let find_max = y_edge == rough_max;
+        let widened: Vec<(f64, f64)> = (1..=72)
+            .map(|k| x_edge * 1.1_f64.powi(k))
+            .filter_map(|x| safe_eval(&func, x).map(|y| (x, y)))
+            .collect();
+        let better = |a: f64, b: f64| if find_max { a > b } else { a < b };
+        let Some(best) = (0..widened.len()).filter(|&i| better(widened[i].1, y_edge))
+            .reduce(|i, j| if better(widened[j].1, widened[i].1) { j } else { i }) else { continue };
+        let inside = if best > 0 { widened[best - 1].0 } else { x_edge };
+        let outside = widened.get(best + 1).map_or(widened[best].0, |&(x, _)| x);
+        let refined = brent_minimize(func, inside.min(outside), inside.max(outside), find_max, &config.solver)
+            .map_or(widened[best].1, |(_, y)| if better(y, widened[best].1) { y } else { widened[best].1 });
+        if find_max { rough_max = rough_max.max(refined); } else { rough_min = rough_min.min(refined); }
+        if best == widened.len() - 1 && unclassified_ends.contains(&end) {
+            clipped_edges.push(widened[best].0);
+        }
+    }
+    
+    // Check for asymptotic behavior near singularities, the window's ends and
+    // open domain ends such as ln(x)/sqrt(x)'s blow-up at 0+
+    let window_ends = restriction.iter().flat_map(|w| [w.lo, w.hi]);
+    let open_ends = match &domain {
+        Domain::Interval(part) => vec![(part.lo, part.lo_open), (part.hi, part.hi_open)],
+        _ => Vec::new(),
+    };
+    let open_ends = open_ends.into_iter().filter(|&(_, open)| open).map(|(end, _)| end);
+    let poles: Vec<f64> = denom_zeros.iter().cloned().chain(window_ends).chain(open_ends).filter(|end| end.is_finite()).collect();
+    let mut pole_limits = Vec::new();
+    for &z in &poles {
+        let mut sides = Vec::new();
+        for side in [-1.0, 1.0] {
+            match pole_divergence(&func, z, side) {
+                Some(lim) if lim > 0.0 => has_inf_pos = true,
+                Some(_) => has_inf_neg = true,
+                None => sides.extend(pole_side_limit(&func, z, side)),
+            }
+        }
+        // A removable singularity, like x = 1 in (x^2-1)/(x-1): both sides settle
+        // on one value, and averaging cancels the opposite rounding of the two
+        match sides[..] {
+            [l, r] if (l - r).abs() < 1e-6 => pole_limits.push(round_to_nice(0.5 * (l + r))),
+            _ => pole_limits.extend(sides),
+        }
+    }
+    
+    // Find horizontal asymptotes (excluded range values)
+    let h_asymptotes = match &rational {
+        Some((p, q)) => match rational_asymptote(p, q) {
+            RationalAsymptote::Horizontal(value) => vec![round_to_nice(value)],
+            _ => Vec::new(),
+        },
+        None => find_horizontal_asymptotes(&func, &config.solver),
+    };
+    
+    // Values swept between the outermost sample and the end limit, like
+    // (0, 0.001157) for sqrt(x)/(x-300) as x -> oo; no range gap lies inside
+    // one. The probed limit is snapped onto the asymptote it approximates.
+    let end_tails: Vec<(f64, f64)> = end_tails.into_iter()
+        .map(|(lim, y)| {
+            let lim = h_asymptotes.iter().copied()
+                .filter(|a| (a - lim).abs() < (y - lim).abs())
+                .min_by(|a, b| (a - lim).abs().total_cmp(&(b - lim).abs()))
+                .unwrap_or(lim);
+            (lim.min(y), lim.max(y))
+        })
+        .collect();
+    
+    // Check if asymptote is actually achieved
+    let mut excluded_range_values: Vec<f64> = Vec::new();
+    // Finite one-sided limits at a pole (atan(1/x) -> +-pi/2) are excluded too
+    for &lim in &pole_limits {
+        let known = h_asymptotes.iter().chain(&excluded_range_values).any(|&v| (v - lim).abs() < 1e-9);
+        if !known && !is_value_achievable(&func, lim, &denom_zeros) {
+            excluded_range_values.push(lim);
+        }
+    }
+    for &asym in &h_asymptotes {
+        if expired() {
+            return Some(partial_result(domain, rough_min, rough_max));
+        }
+        if !is_value_achievable(&func, asym, &denom_zeros) {
+            excluded_range_values.push(asym);
+        }
+    }
+    
+    // Special case handling; the table describes natural domains only
+    let func_lower = if restriction.is_some() { String::new() } else { func_str.to_lowercase().replace(" ", "") };
+    
+    // Sums of abs(x - a_i): exact minimum at one of the breakpoints
+    let abs_sum_min = tree.as_ref().filter(|_| restriction.is_none()).and_then(abs_sum_breakpoints).map(|breakpoints| {
+        breakpoints.iter()
+            .filter_map(|&b| safe_eval(&func, b))
+            .fold(f64::INFINITY, f64::min)
+    });
+    if let Some(min) = abs_sum_min {
+        has_inf_pos = true; has_inf_neg = false;
+        rough_min = min;
+    }
+    
+    // Quadratics a*x^2 + b*x + c: the vertex value c - b^2/(4a) is attained exactly,
+    // so perfect squares like x^2 - 2*x + 1 get a closed 0 rather than a grid minimum
+    let quadratic_vertex = tree.as_ref().filter(|_| restriction.is_none()).and_then(as_polynomial)
+        .filter(|p| p.len() == 3 && p[2] != 0.0)
+        .map(|p| (p[0] - p[1] * p[1] / (4.0 * p[2]), p[2] > 0.0));
+    if let Some((vertex, opens_up)) = quadratic_vertex {
+        has_inf_pos = opens_up; has_inf_neg = !opens_up;
+        if opens_up { rough_min = vertex; } else { rough_max = vertex; }
+    }
+    
+    // Odd-degree polynomials run from -oo to oo whatever their turning points do
+    let odd_polynomial = tree.as_ref().filter(|_| restriction.is_none()).and_then(as_polynomial)
+        .is_some_and(|p| p.len() >= 4 && p.len() % 2 == 0);
+    if odd_polynomial {
+        has_inf_pos = true; has_inf_neg = true;
+    }
+    
+    // Single occurrence of x: propagate the exact range through the tree
+    let var_span = match (&restriction, &domain) {
+        (None, _) => Some(Interval::reals()),
+        (Some(_), Domain::Interval(part)) => Some(*part),
+        _ => None,
+    };
+    let composed = tree.as_ref()
+        .filter(|t| count_vars(t) == 1)
+        .zip(var_span)
+        .and_then(|(t, var)| composition_range(t, &var))
+        .filter(|span| !span.lo.is_nan() && !span.hi.is_nan());
+    // Two sinusoids with incommensurate frequencies: bounded, but not periodic
+    let quasi_periodic = tree.as_ref().filter(|_| restriction.is_none() && composed.is_none()).and_then(quasi_periodic_range);
+    let composed = composed.or(quasi_periodic);
+    if let Some(span) = composed {
+        has_inf_neg = span.lo == f64::NEG_INFINITY; has_inf_pos = span.hi == f64::INFINITY;
+        rough_min = span.lo; rough_max = span.hi;
+    }
+    
+    // Determine final range
+    let snap_zero = |v: f64| if v.abs() < config.solver.zero_threshold { 0.0 } else { v };
+    let mut final_min = if has_inf_neg { f64::NEG_INFINITY } else { round_to_nice(snap_zero(rough_min)) };
+    let mut final_max = if has_inf_pos { f64::INFINITY } else { round_to_nice(snap_zero(rough_max)) };
+    
+    // Bounds the sampled values only creep toward snap onto the unattained asymptote
+    if composed.is_none() {
+        for &v in &excluded_range_values {
+            let tol = 1e-4 * v.abs().max(1.0);
+            if final_min.is_finite() && rough_min >= v - 1e-12 && rough_min - v < tol { final_min = v; }
+            if final_max.is_finite() && rough_max <= v + 1e-12 && v - rough_max < tol { final_max = v; }
+        }
+    }
+    
+    // Determine open/closed
+    let mut min_open = final_min == f64::NEG_INFINITY;
+    let mut max_open = final_max == f64::INFINITY;
+    
+    // A bound that is only approached asymptotically is not attained
+    for &v in &excluded_range_values {
+        if (v - final_min).abs() < 1e-9 { min_open = true; }
+        if (v - final_max).abs() < 1e-9 { max_open = true; }
+    }
+    if abs_sum_min.is_some() {
+        min_open = false;
+    }
+    if let Some((_, opens_up)) = quadratic_vertex {
+        if opens_up { min_open = false; } else { max_open = false; }
+    }
+    if let Some(span) = composed {
+        min_open = span.lo_open; max_open = span.hi_open;
+    }
+    
+    // Determine range type
+    let integer_valued = !func_lower.is_empty() && tree.as_ref().is_some_and(|t| count_vars(t) > 0 && is_integer_valued(t));
+    let mut range_type = if composed.is_some() {
+        RangeType::Simple
+    } else {
+        determine_range_type(integer_valued, &denom_zeros, &excluded_range_values, has_inf_pos, has_inf_neg)
+    };
+    
+    // Values skipped between branches, e.g. (-1, 2) for (2x^2+1)/(x^2-1). Not on
+    // a sparse domain, where neighbouring samples sit on separate pieces and
+    // can't corroborate a gap, nor on a domain only resolved on a window.
+    if composed.is_none() && abs_sum_min.is_none() && quadratic_vertex.is_none() && !odd_polynomial && !sparse && !window_domain
+        && !matches!(domain, Domain::IntegerSet)
+        && matches!(range_type, RangeType::Simple | RangeType::CustomUnion { .. })
+    {
+        // Edges only reached as a one-sided limit at a jump stay open, like asymptotes
+        let mut unreached = excluded_range_values.clone();
+        let gaps: Vec<(f64, f64)> = detect_range_gaps(&func, &samples, config).into_iter()
+            .filter_map(|gap| trim_gap_by_tails(gap, &end_tails))
+            .map(|(lo, hi)| {
+                let (lo, lo_attained) = refine_gap_edge(&func, &samples, lo, true, &config.solver);
+                let (hi, hi_attained) = refine_gap_edge(&func, &samples, hi, false, &config.solver);
+                if !lo_attained { unreached.push(round_to_nice(lo)); }
+                if !hi_attained { unreached.push(round_to_nice(hi)); }
+                (lo, hi)
+            })
+            .collect();
+        let splits = excluded_range_values.iter().any(|&v| v > final_min && v < final_max);
+        if !gaps.is_empty() || splits {
+            let parts = range_parts_with_gaps(Interval::new(final_min, final_max, min_open, max_open), &gaps, &unreached, config.level_tolerance, output_scale(&samples, config));
+            range_type = RangeType::CustomUnion { parts };
+        }
+    }
+    
+    // Periodic poles with one sign per branch: (-oo, -b] U [b, oo), the closed
+    // ends found rather than assumed
+    if composed.is_none() && has_inf_pos && has_inf_neg
+        && matches!(&domain, Domain::PeriodicComplement { pattern } if pattern.ends_with("Integers)"))
+    {
+        if let Some((bound, closed)) = periodic_exterior(&func, &denom_zeros, &config.solver) {
+            final_min = f64::NEG_INFINITY; final_max = f64::INFINITY;
+            range_type = RangeType::UnionExterior { bound, closed };
+        }
+    }
+    
+    // Step-valued functions: the sampled levels plus the values at the jumps.
+    // On an integer domain the samples are the whole domain near the origin.
+    // Anything else whose samples cluster onto a few levels (floor(x/2) on a
+    // bounded window) is discrete too, as long as each level recurs.
+    let finite_levels = tree.as_ref()
+        .filter(|t| count_vars(t) > 0)
+        .map(|t| {
+            let at_jumps: Vec<f64> = step_points(t).into_iter().filter_map(|x| safe_eval(&func, x)).collect();
+            let mut levels: Vec<f64> = values.iter().chain(&at_jumps).map(|&v| round_to_nice(v)).collect();
+            levels.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let levels = cluster_levels(&levels, config.level_tolerance);
+            let structural = is_step_valued(t) || matches!(domain, Domain::IntegerSet);
+            (levels, structural)
+        })
+        .filter(|(levels, structural)| *structural || values.len() >= 4 * levels.len())
+        .map(|(levels, _)| levels)
+        .filter(|levels| !levels.is_empty() && levels.len() <= MAX_FINITE_LEVELS);
+    if let Some(levels) = &finite_levels {
+        final_min = levels[0]; final_max = levels[levels.len() - 1];
+        min_open = false; max_open = false;
+        range_type = RangeType::FiniteSet(levels.clone());
+    } else if matches!(domain, Domain::IntegerSet) {
+        warnings.push("values only occur at integer x; range shown as their interval hull".to_string());
+    } else if !integer_valued && (func_lower.contains("floor") || func_lower.contains("ceil")) && range_type == RangeType::Simple {
+        warnings.push("floor/ceil jumps may skip values; range shown as their interval hull".to_string());
+    }
+    
+    // Two sinusoids with an unknown frequency ratio may never peak together,
+    // so the sampled ends can't be called attained
+    if quasi_periodic.is_none() && restriction.is_none() && range_type == RangeType::Simple
+        && tree.as_ref().is_some_and(has_unclassified_frequencies)
+    {
+        min_open = true; max_open = true;
+        warnings.push("frequency ratio of the two sinusoids could not be classified; sampled ends shown open".to_string());
+    }
+    
+    // Tabulated exact ranges take precedence over everything derived above
+    let tabulated = SPECIAL_CASES.iter().find(|(key, _)| *key == func_lower).map(|(_, entry)| entry.to_range());
+    if let Some(range) = &tabulated {
+        final_min = range.hull.lo; final_max = range.hull.hi;
+        min_open = range.hull.lo_open; max_open = range.hull.hi_open;
+        range_type = range.range_type.clone();
+    }
+    
+    let method = if tabulated.is_some() {
+        "Exact (table)".to_string()
+    } else if window_domain {
+        "Hybrid Analysis".to_string()
+    } else if finite_levels.is_some() {
+        "Exact (finite set)".to_string()
+    } else if abs_sum_min.is_some() {
+        "Exact (piecewise linear)".to_string()
+    } else if quasi_periodic.is_some() {
+        "Exact (quasi-periodic)".to_string()
+    } else if composed.is_some() {
+        "Exact (composition)".to_string()
+    } else if quadratic_vertex.is_some() {
+        "Exact (quadratic)".to_string()
+    } else if odd_polynomial {
+        "Exact (odd polynomial)".to_string()
+    } else if !excluded_range_values.is_empty() || !denom_zeros.is_empty() {
+        "Exact (function_range)".to_string()
+    } else {
+        "Hybrid Analysis".to_string()
+    };
+    
+    // A bounded oscillation has no limit but its samples cover a full period;
+    // unbounded growth read off the samples alone is the doubtful case
+    if !method.starts_with("Exact") && (has_inf_pos || has_inf_neg) {
+        for end in unclassified_ends {
+            warnings.push(format!("limit toward {} could not be classified; end behavior taken from samples", format_symbolic(end)));
+        }
+    }
+    if !method.starts_with("Exact") {
+        for x in clipped_edges {
+            warnings.push(format!("extreme still at the edge of the widened scan near x = {}; the true bound may lie beyond it", format_symbolic(x)));
+        }
+    }
+    
+    Some(SolveResult {
+        domain,
+        range: Range {
+            hull: Interval::new(final_min, final_max, min_open, max_open),
+            range_type,
+        },
+        method,
+        warnings,
+    })
+}
+
+fn determine_range_type(integer_valued: bool, _denom_zeros: &[f64], excluded_range_values: &[f64], has_inf_pos: bool, has_inf_neg: bool) -> RangeType {
+    // floor/ceil, unless a continuous term rides along (floor(x) + x)
+    if integer_valued {
+        return RangeType::Integers;
+    }
+    
+    // Functions with excluded values
+    if !excluded_range_values.is_empty() && has_inf_pos && has_inf_neg {
+        let mut parts = Vec::new();
+        let mut sorted_excl = excluded_range_values.to_vec();
+        sorted_excl.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        
+        parts.push(Interval::new(f64::NEG_INFINITY, sorted_excl[0], true, true));
+        for i in 0..sorted_excl.len() - 1 {
+            parts.push(Interval::new(sorted_excl[i], sorted_excl[i + 1], true, true));
+        }
+        parts.push(Interval::new(sorted_excl[sorted_excl.len() - 1], f64::INFINITY, true, true));
+        
+        return RangeType::CustomUnion { parts };
+    }
+    
+    RangeType::Simple
+}
+
+// =============================================================================
+// SAMPLING DIAGNOSTICS - Raw views of the evaluation grid
+// =============================================================================
+
+/// Finite values of the function on the solver's grid over its detected domain.
+/// Empty if the expression doesn't parse.
+pub fn sample_values(func_str: &str, config: &GridConfig) -> Vec<f64> {
+    let Some(func_str) = canonical_variable(func_str).map(|s| rewrite_odd_roots(&s)) else { return Vec::new() };
+    let Ok(expr) = func_str.parse::<Expr>() else { return Vec::new() };
+    let Ok(func) = bind_x(expr.clone()) else { return Vec::new() };
+    let denom_zeros = find_denominator_zeros(&func_str, &func, config);
+    let domain = detect_domain(&func_str, &func, config);
+    let mut grid = generate_smart_grid(&domain, &denom_zeros, &[]);
+    sample_smart_grid(&expr, &mut grid, uses_coarse_grid(&domain, &denom_zeros, &[])).into_iter().map(|(_, y)| y).collect()
+}
+
+/// Smallest and largest finite value over the full-resolution smart grid, each
+/// point evaluated as the grid generates it, so neither the points nor the
+/// values are held in memory. None if the expression doesn't parse or no point
+/// gives a finite value.
+pub fn streamed_min_max(func_str: &str, config: &GridConfig) -> Option<(f64, f64)> {
+    let func_str = rewrite_odd_roots(&canonical_variable(func_str)?);
+    let func = bind_x(func_str.parse::<Expr>().ok()?).ok()?;
+    let denom_zeros = find_denominator_zeros(&func_str, &func, config);
+    let domain = detect_domain(&func_str, &func, config);
+    smart_grid_points(&domain, &denom_zeros, &[], false)
+        .map(&func)
+        .filter(|&y| is_valid(y))
+        .fold(None, |bounds, y| match bounds {
+            None => Some((y, y)),
+            Some((lo, hi)) => Some((y.min(lo), y.max(hi))),
+        })
+}
+
+/// Cross-check of an exact range against the numbers: the sampled values and a
+/// Brent sweep must stay inside the hull and come within reach of its finite
+/// bounds. One line per disagreement, empty when they agree or no exact path
+/// applied. None if the expression doesn't parse.
+pub fn validate_range(func_str: &str, config: &GridConfig) -> Option<Vec<String>> {
+    let result = solve_with_config(func_str, config)?;
+    if !result.method.starts_with("Exact") {
+        return Some(Vec::new());
+    }
+    let values = sample_values(func_str, config);
+    let sweep = canonical_variable(func_str).map(|s| rewrite_odd_roots(&s)).and_then(|s| brent_sweep(&s, &result.domain, -100.0, 100.0, 20, &config.solver));
+    let lo = values.iter().cloned().chain(sweep.map(|(min, _)| min)).fold(f64::INFINITY, f64::min);
+    let hi = values.iter().cloned().chain(sweep.map(|(_, max)| max)).fold(f64::NEG_INFINITY, f64::max);
+    if !lo.is_finite() && !hi.is_finite() {
+        return Some(Vec::new());
+    }
+    
+    let hull = result.range.hull;
+    let slack = |bound: f64, rel: f64| rel * bound.abs().max(1.0);
+    let mut discrepancies = Vec::new();
+    if lo < hull.lo - slack(hull.lo, 1e-6) {
+        discrepancies.push(format!("sampled minimum {} lies below the exact lower bound {}", lo, format_symbolic(hull.lo)));
+    }
+    if hi > hull.hi + slack(hull.hi, 1e-6) {
+        discrepancies.push(format!("sampled maximum {} lies above the exact upper bound {}", hi, format_symbolic(hull.hi)));
+    }
+    if hull.lo.is_finite() && lo > hull.lo + slack(hull.lo, 1e-3) {
+        discrepancies.push(format!("exact lower bound {} is never approached; samples stop at {}", format_symbolic(hull.lo), lo));
+    }
+    if hull.hi.is_finite() && hi < hull.hi - slack(hull.hi, 1e-3) {
+        discrepancies.push(format!("exact upper bound {} is never approached; samples stop at {}", format_symbolic(hull.hi), hi));
+    }
+    Some(discrepancies)
+}
+
+/// Histogram of the sampled values as (bin center, count) pairs over
+/// `n_bins` equal bins spanning the smallest to the largest value. Shows where
+/// the function spends its output, e.g. near-gaps or mass far from zero.
+pub fn value_histogram(func_str: &str, n_bins: usize, config: &GridConfig) -> Vec<(f64, usize)> {
+    let values = sample_values(func_str, config);
+    if values.is_empty() || n_bins == 0 {
+        return Vec::new();
+    }
+    let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let width = (max - min) / n_bins as f64;
+    let mut counts = vec![0usize; n_bins];
+    for &y in &values {
+        // A constant function has zero width and lands entirely in the first bin
+        let bin = if width > 0.0 { ((y - min) / width) as usize } else { 0 };
+        counts[bin.min(n_bins - 1)] += 1;
+    }
+    counts.into_iter()
+        .enumerate()
+        .map(|(i, count)| (min + (i as f64 + 0.5) * width, count))
+        .collect()
+}
+
+// =============================================================================
+// SPECIAL CASES - Ranges the generic analysis can't pin down
+// =============================================================================
+
+/// Shape of a tabulated range
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OverrideShape {
+    /// The single interval from `min` to `max`
+    Interval,
+    /// (-oo, -bound] U [bound, oo), or open at the bound
+    Exterior { bound: f64, closed: bool },
+    /// Disjoint pieces
+    Union(&'static [Interval]),
+}
+
+/// Exact range of one tabulated expression
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RangeOverride {
+    pub hull: Interval,
+    pub shape: OverrideShape,
+}
+
+impl RangeOverride {
+    const fn interval(min: f64, max: f64, min_open: bool, max_open: bool) -> Self {
+        RangeOverride { hull: Interval::new(min, max, min_open, max_open), shape: OverrideShape::Interval }
+    }
+
+    pub fn to_range(&self) -> Range {
+        let range_type = match self.shape {
+            OverrideShape::Interval => RangeType::Simple,
+            OverrideShape::Exterior { bound, closed } => RangeType::UnionExterior { bound, closed },
+            OverrideShape::Union(parts) => RangeType::CustomUnion { parts: parts.to_vec() },
+        };
+        Range { hull: self.hull, range_type }
+    }
+}
+
+const INF: f64 = f64::INFINITY;
+
+/// Expressions (lowercased, spaces removed) whose range the generic analysis
+/// gets wrong, with the exact answer. Keep this list short: an entry should go
+/// once the general machinery handles it.
+pub const SPECIAL_CASES: &[(&str, RangeOverride)] = &[
+    // Essential singularity: 1 is the limit at +-oo and never attained
+    ("exp(1/x)", RangeOverride { hull: Interval::new(0.0, INF, true, true),
+        shape: OverrideShape::Union(&[Interval::new(0.0, 1.0, true, true), Interval::new(1.0, INF, true, true)]) }),
+    // Supremum 1 is the removable value at x = 0
+    ("ln(1+x^2)/x^2", RangeOverride::interval(0.0, 1.0, true, false)),
+];
+
+// =============================================================================
+// PREPROCESSING
+// =============================================================================
+
+/// Spellings accepted on input, mapped to the names meval knows
+const FUNCTION_ALIASES: &[(&str, &str)] = &[
+    ("arcsin", "asin"), ("arccos", "acos"), ("arctan", "atan"),
+    ("arcsinh", "asinh"), ("arccosh", "acosh"), ("arctanh", "atanh"),
+    ("arsinh", "asinh"), ("arcosh", "acosh"), ("artanh", "atanh"),
+    ("log", "ln"),
+];
+
+/// Unicode math symbols pasted from documents, mapped to ASCII before lexing.
+/// Names are padded with spaces so `2πx` splits into `2 pi x`.
+const UNICODE_OPERATORS: &[(char, &str)] = &[
+    ('\u{2212}', "-"), ('\u{2013}', "-"),
+    ('\u{00d7}', "*"), ('\u{00b7}', "*"), ('\u{22c5}', "*"), ('\u{2217}', "*"),
+    ('\u{00f7}', "/"), ('\u{2215}', "/"),
+    ('\u{221a}', " sqrt "), ('\u{03c0}', " pi "),
+    ('\u{00a0}', " "), ('\u{202f}', " "), ('\u{200b}', ""),
+];
+
+fn normalize_unicode(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for c in input.chars() {
+        match UNICODE_OPERATORS.iter().find(|(symbol, _)| *symbol == c) {
+            Some((_, ascii)) => out.push_str(ascii),
+            None => out.push(c),
+        }
+    }
+    out
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum RawToken {
+    Number(String),
+    Ident(String),
+    Open,
+    Close,
+    Symbol(String),
+}
+
+fn lex_expr(input: &str) -> Vec<RawToken> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || (c == '.' && chars.get(i + 1).is_some_and(|d| d.is_ascii_digit())) {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            // Scientific notation only when digits follow the 'e', so 2e stays 2*e
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                let sign = usize::from(matches!(chars.get(i + 1), Some('+') | Some('-')));
+                if chars.get(i + 1 + sign).is_some_and(|d| d.is_ascii_digit()) {
+                    i += 1 + sign;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            tokens.push(RawToken::Number(chars[start..i].iter().collect()));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(RawToken::Ident(chars[start..i].iter().collect()));
+        } else if c == '(' {
+            tokens.push(RawToken::Open);
+            i += 1;
+        } else if c == ')' {
+            tokens.push(RawToken::Close);
+            i += 1;
+        } else if c == '*' && chars.get(i + 1) == Some(&'*') {
+            tokens.push(RawToken::Symbol("^".to_string()));
+            i += 2;
+        } else {
+            tokens.push(RawToken::Symbol(c.to_string()));
+            i += 1;
+        }
+    }
+    tokens
+}
+
+/// Rewrite two-argument `ln(b, x)` (written `log(b, x)`) as `(ln(x)/ln(b))`,
+/// since meval's `ln` takes a single argument. Nested calls are rewritten too.
+fn rewrite_log_base(tokens: &[RawToken]) -> Vec<RawToken> {
+    let ln = || RawToken::Ident("ln".to_string());
+    let mut out = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+    while i < tokens.len() {
+        if tokens[i] == ln() && tokens.get(i + 1) == Some(&RawToken::Open) {
+            // Find the matching close paren; unbalanced input is left for meval to reject
+            let mut depth = 0;
+            let close = tokens[i + 1..].iter().position(|t| {
+                match t {
+                    RawToken::Open => depth += 1,
+                    RawToken::Close => depth -= 1,
+                    _ => {}
+                }
+                depth == 0
+            });
+            if let Some(offset) = close {
+                let inner = rewrite_log_base(&tokens[i + 2..i + 1 + offset]);
+                let mut depth = 0;
+                let comma = inner.iter().position(|t| {
+                    match t {
+                        RawToken::Open => depth += 1,
+                        RawToken::Close => depth -= 1,
+                        _ => {}
+                    }
+                    depth == 0 && *t == RawToken::Symbol(",".to_string())
+                });
+                match comma {
+                    Some(c) => {
+                        let (base, arg) = (&inner[..c], &inner[c + 1..]);
+                        out.extend([RawToken::Open, ln(), RawToken::Open]);
+                        out.extend_from_slice(arg);
+                        out.extend([RawToken::Close, RawToken::Symbol("/".to_string()), ln(), RawToken::Open]);
+                        out.extend_from_slice(base);
+                        out.extend([RawToken::Close, RawToken::Close]);
+                    }
+                    None => {
+                        out.extend([ln(), RawToken::Open]);
+                        out.extend(inner);
+                        out.push(RawToken::Close);
+                    }
+                }
+                i += offset + 2;
+                continue;
+            }
+        }
+        out.push(tokens[i].clone());
+        i += 1;
+    }
+    out
+}
+
+/// Parenthesize the operand of a bare `sqrt`, as left by `√x` or `√2`. The
+/// operand is one number or name, or a call such as `sin(x)` up to its close paren.
+fn wrap_bare_sqrt(tokens: &[RawToken]) -> Vec<RawToken> {
+    let mut out = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+    while i < tokens.len() {
+        out.push(tokens[i].clone());
+        let bare = tokens[i] == RawToken::Ident("sqrt".to_string())
+            && matches!(tokens.get(i + 1), Some(RawToken::Number(_) | RawToken::Ident(_)));
+        if !bare {
+            i += 1;
+            continue;
+        }
+        let mut end = i + 2;
+        if matches!(tokens[i + 1], RawToken::Ident(_)) && tokens.get(end) == Some(&RawToken::Open) {
+            let mut depth = 0;
+            if let Some(offset) = tokens[end..].iter().position(|t| {
+                match t {
+                    RawToken::Open => depth += 1,
+                    RawToken::Close => depth -= 1,
+                    _ => {}
+                }
+                depth == 0
+            }) {
+                end += offset + 1;
+            }
+        }
+        out.push(RawToken::Open);
+        out.extend(wrap_bare_sqrt(&tokens[i + 1..end]));
+        out.push(RawToken::Close);
+        i = end;
+    }
+    out
+}
+
+/// `cosh(u) + sinh(u)` as `exp(u)` and `cosh(u) - sinh(u)` as `exp(-u)`, and
+/// the same with the terms swapped. The two terms grow alike, so their
+/// difference cancels to rounding noise in floating point well before either
+/// overflows. Likewise tanh written out, `(exp(u) - exp(-u))/(exp(u) + exp(-u))`,
+/// as `tanh(u)`: both sides overflow past |u| = 709 and the quotient is inf/inf.
+/// None when the expression has no such pair.
+fn rewrite_hyperbolic_pairs(func_str: &str) -> Option<String> {
+    fn fold(node: &Node) -> Node {
+        let unary = |name: &str, arg: Node| Node::Func(name.to_string(), vec![arg]);
+        let neg = |a: Node| Node::Neg(Box::new(a));
+        let pair = |l: &Node, r: &Node| match (l, r) {
+            (Node::Func(f, a), Node::Func(g, b)) if a.len() == 1 && a == b => Some((f.clone(), g.clone(), a[0].clone())),
+            _ => None,
+        };
+        match node {
+            Node::Add(l, r) => {
+                let (l, r) = (fold(l), fold(r));
+                match pair(&l, &r) {
+                    Some((f, g, u)) if matches!((f.as_str(), g.as_str()), ("cosh", "sinh") | ("sinh", "cosh")) => unary("exp", u),
+                    _ => Node::Add(Box::new(l), Box::new(r)),
+                }
+            }
+            Node::Sub(l, r) => {
+                let (l, r) = (fold(l), fold(r));
+                match pair(&l, &r) {
+                    Some((f, g, u)) if f == "cosh" && g == "sinh" => unary("exp", neg(u)),
+                    Some((f, g, u)) if f == "sinh" && g == "cosh" => neg(unary("exp", neg(u))),
+                    _ => Node::Sub(Box::new(l), Box::new(r)),
+                }
+            }
+            Node::Num(_) | Node::Var(_) => node.clone(),
+            Node::Neg(a) => neg(fold(a)),
+            Node::Mul(l, r) => Node::Mul(Box::new(fold(l)), Box::new(fold(r))),
+            Node::Div(l, r) => {
+                let (l, r) = (fold(l), fold(r));
+                // The numerator may carry a factor, as in x*(exp(x) - exp(-x))/(...)
+                let (factor, top) = match &l {
+                    Node::Mul(k, top) => (Some(k.as_ref()), top.as_ref()),
+                    top => (None, top),
+                };
+                let tanh = match (top, &r) {
+                    (Node::Sub(a, b), Node::Add(c, d)) => match (exp_pair(a, b), exp_pair(c, d)) {
+                        (Some((u, v)), Some((p, q))) if (p == u && q == v) || (p == v && q == u) => Some(unary("tanh", u)),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                match (tanh, factor) {
+                    (Some(t), Some(k)) => Node::Mul(Box::new(k.clone()), Box::new(t)),
+                    (Some(t), None) => t,
+                    (None, _) => Node::Div(Box::new(l), Box::new(r)),
+                }
+            }
+            Node::Pow(l, r) => Node::Pow(Box::new(fold(l)), Box::new(fold(r))),
+            Node::Func(name, args) => Node::Func(name.clone(), args.iter().map(fold).collect()),
+        }
+    }
+    
+    /// (u, -u) for `exp(u)` (or `e^u`) beside `exp(-u)`
+    fn exp_pair(a: &Node, b: &Node) -> Option<(Node, Node)> {
+        let arg = |n: &Node| match n {
+            Node::Func(f, args) if f == "exp" && args.len() == 1 => Some(args[0].clone()),
+            Node::Pow(base, u) if matches!(base.as_ref(), Node::Num(b) if *b == E) => Some(u.as_ref().clone()),
+            _ => None,
+        };
+        let (u, v) = (arg(a)?, arg(b)?);
+        let negated = |x: &Node, y: &Node| matches!(y, Node::Neg(inner) if **inner == *x);
+        (negated(&u, &v) || negated(&v, &u)).then_some((u, v))
+    }
+    
+    let tree = build_tree(&func_str.parse::<Expr>().ok()?)?;
+    let folded = fold(&tree);
+    (folded != tree).then(|| canonical_node(&folded))
+}
+
+/// Rewrite `b^(1/n)` with an odd integer `n` as `root(b,n)`, so odd roots of
+/// negative numbers stay real instead of becoming NaN under `powf`.
+fn rewrite_odd_roots(func_str: &str) -> String {
+    let tokens = lex_expr(func_str);
+    let symbol = |s: &str| RawToken::Symbol(s.to_string());
+    let mut out: Vec<RawToken> = Vec::with_capacity(tokens.len());
+    let mut i = 0;
+    while i < tokens.len() {
+        let odd_root = match tokens.get(i..i + 6) {
+            Some([caret, RawToken::Open, RawToken::Number(one), slash, RawToken::Number(n), RawToken::Close])
+                if *caret == symbol("^") && *slash == symbol("/") && one == "1" && tokens.get(i + 6) != Some(&symbol("^")) =>
+            {
+                n.parse::<u32>().ok().filter(|n| n % 2 == 1 && *n > 1)
+            }
+            _ => None,
+        };
+        // The base is the operand just emitted: a number, a name, or a parenthesized group with its function name
+        let base_start = match (odd_root, out.last()) {
+            (Some(_), Some(RawToken::Number(_) | RawToken::Ident(_))) => Some(out.len() - 1),
+            (Some(_), Some(RawToken::Close)) => {
+                let mut depth = 0;
+                out.iter().rposition(|t| {
+                    match t {
+                        RawToken::Close => depth += 1,
+                        RawToken::Open => depth -= 1,
+                        _ => {}
+                    }
+                    depth == 0
+                }).map(|open| if open > 0 && matches!(out[open - 1], RawToken::Ident(_)) { open - 1 } else { open })
+            }
+            _ => None,
+        };
+        match (odd_root, base_start) {
+            (Some(n), Some(start)) => {
+                let base = out.split_off(start);
+                out.extend([RawToken::Ident("root".to_string()), RawToken::Open]);
+                out.extend(base);
+                out.extend([symbol(","), RawToken::Number(n.to_string()), RawToken::Close]);
+                i += 6;
+            }
+            _ => {
+                out.push(tokens[i].clone());
+                i += 1;
+            }
+        }
+    }
+    out.iter()
+        .map(|t| match t {
+            RawToken::Number(text) | RawToken::Ident(text) | RawToken::Symbol(text) => text.as_str(),
+            RawToken::Open => "(",
+            RawToken::Close => ")",
+        })
+        .collect()
+}
+
+/// Normalize user input into the syntax meval parses: `**` becomes `^`,
+/// function aliases (`arcsin`, `log`, ...) are renamed, `log(b, x)` becomes
+/// `(ln(x)/ln(b))`, implicit products such as `2x`, `3sin(x)` and
+/// `(x+1)(x-1)` get an explicit `*`, and whitespace is dropped. Unicode
+/// operators (`−`, `×`, `·`, `÷`, `√`, `π`) become their ASCII spellings first.
+/// Normalizing an already normalized string returns it unchanged.
+///
+/// Signs are left alone: meval already gives unary minus a lower precedence
+/// than `^`, so `-x^2` means `-(x^2)` and `--x` means `x`, as in written math.
+pub fn preprocess_expr(input: &str) -> String {
+    let mut tokens = lex_expr(&normalize_unicode(input));
+    for token in tokens.iter_mut() {
+        if let RawToken::Ident(name) = token {
+            if let Some((_, canonical)) = FUNCTION_ALIASES.iter().find(|(alias, _)| alias == name) {
+                *name = canonical.to_string();
+            }
+        }
+    }
+    let tokens = wrap_bare_sqrt(&rewrite_log_base(&tokens));
+    
+    let is_value_name = |name: &str| matches!(name, "x" | "pi" | "e");
+    let mut out = String::with_capacity(input.len());
+    for (i, token) in tokens.iter().enumerate() {
+        if i > 0 {
+            let ends_operand = match &tokens[i - 1] {
+                RawToken::Number(_) | RawToken::Close => true,
+                RawToken::Ident(name) => is_value_name(name),
+                _ => false,
+            };
+            let starts_operand = matches!(token, RawToken::Number(_) | RawToken::Ident(_) | RawToken::Open);
+            if ends_operand && starts_operand {
+                out.push('*');
+            }
+        }
+        match token {
+            RawToken::Number(text) | RawToken::Ident(text) | RawToken::Symbol(text) => out.push_str(text),
+            RawToken::Open => out.push('('),
+            RawToken::Close => out.push(')'),
+        }
+    }
+    out
+}
+
+// =============================================================================
+// C FFI - Stable ABI for callers outside Rust
+// =============================================================================
+
+/// Shape of the domain reported through `OzonResult::domain_kind`
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OzonDomainKind {
+    Reals = 0,
+    Interval = 1,
+    Union = 2,
+    Complement = 3,
+    PeriodicComplement = 4,
+    Empty = 5,
+    Integers = 6,
+}
+
+/// Shape of the range reported through `OzonResult::range_kind`
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OzonRangeKind {
+    Interval = 0,
+    SplitAtValue = 1,
+    UnionExterior = 2,
+    Integers = 3,
+    Union = 4,
+    FiniteSet = 5,
+}
+
+/// Solver output as plain data. For non-interval kinds the bounds are the
+/// convex hull and the openness flags describe its ends. SplitAtValue's
+/// excluded value and UnionExterior's bound travel in `range_split`. Not
+/// carried: the points a Complement domain excludes, a PeriodicComplement's
+/// lattice and the inner pieces of a union or finite set; `solve` has those.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OzonResult {
+    pub domain_kind: i32,
+    pub domain_min: f64,
+    pub domain_max: f64,
+    pub domain_min_open: bool,
+    pub domain_max_open: bool,
+    pub range_kind: i32,
+    pub range_min: f64,
+    pub range_max: f64,
+    pub range_min_open: bool,
+    pub range_max_open: bool,
+    /// SplitAtValue's excluded value or UnionExterior's bound; NaN otherwise
+    pub range_split: f64,
+    /// Whether UnionExterior's bound is attained
+    pub range_split_closed: bool,
+}
+
+pub const OZON_OK: i32 = 0;
+pub const OZON_ERR_NULL: i32 = 1;
+pub const OZON_ERR_UTF8: i32 = 2;
+pub const OZON_ERR_PARSE: i32 = 3;
+/// The solver panicked; the panic is caught so it never unwinds into the caller
+pub const OZON_ERR_PANIC: i32 = 4;
+
+impl From<&SolveResult> for OzonResult {
+    fn from(result: &SolveResult) -> Self {
+        let (domain_kind, domain_min, domain_max, domain_min_open, domain_max_open) = match &result.domain {
+            Domain::Reals => (OzonDomainKind::Reals, f64::NEG_INFINITY, f64::INFINITY, true, true),
+            Domain::Interval(part) => (OzonDomainKind::Interval, part.lo, part.hi, part.lo_open, part.hi_open),
+            Domain::UnionOfIntervals(parts) => match (parts.first(), parts.last()) {
+                (Some(first), Some(last)) => (OzonDomainKind::Union, first.lo, last.hi, first.lo_open, last.hi_open),
+                _ => (OzonDomainKind::Empty, f64::NAN, f64::NAN, true, true),
+            },
+            Domain::Complement { .. } => {
+                (OzonDomainKind::Complement, f64::NEG_INFINITY, f64::INFINITY, true, true)
+            }
+            Domain::PeriodicComplement { .. } => {
+                (OzonDomainKind::PeriodicComplement, f64::NEG_INFINITY, f64::INFINITY, true, true)
+            }
+            Domain::IntegerSet => (OzonDomainKind::Integers, f64::NEG_INFINITY, f64::INFINITY, true, true),
+            Domain::Empty => (OzonDomainKind::Empty, f64::NAN, f64::NAN, true, true),
+        };
+        
+        let range = &result.range;
+        let (range_split, range_split_closed) = match range.range_type {
+            RangeType::SplitAtValue { excluded } => (excluded, false),
+            RangeType::UnionExterior { bound, closed } => (bound, closed),
+            _ => (f64::NAN, false),
+        };
+        let (range_kind, range_min, range_max, range_min_open, range_max_open) = match &range.range_type {
+            RangeType::Simple => (OzonRangeKind::Interval, range.hull.lo, range.hull.hi, range.hull.lo_open, range.hull.hi_open),
+            RangeType::SplitAtValue { .. } => {
+                (OzonRangeKind::SplitAtValue, f64::NEG_INFINITY, f64::INFINITY, true, true)
+            }
+            RangeType::UnionExterior { .. } => {
+                (OzonRangeKind::UnionExterior, f64::NEG_INFINITY, f64::INFINITY, true, true)
+            }
+            RangeType::Integers => (OzonRangeKind::Integers, f64::NEG_INFINITY, f64::INFINITY, true, true),
+            RangeType::FiniteSet(_) => (OzonRangeKind::FiniteSet, range.hull.lo, range.hull.hi, false, false),
+            RangeType::CustomUnion { parts } => match (parts.first(), parts.last()) {
+                (Some(first), Some(last)) => (OzonRangeKind::Union, first.lo, last.hi, first.lo_open, last.hi_open),
+                _ => (OzonRangeKind::Union, f64::NAN, f64::NAN, true, true),
+            },
+        };
+        
+        OzonResult {
+            domain_kind: domain_kind as i32,
+            domain_min,
+            domain_max,
+            domain_min_open,
+            domain_max_open,
+            range_kind: range_kind as i32,
+            range_min,
+            range_max,
+            range_min_open,
+            range_max_open,
+            range_split,
+            range_split_closed,
+        }
+    }
+}
+
+/// Solve a NUL-terminated expression and write the result into `out`.
+/// Returns `OZON_OK` on success; `out` is left untouched on any error.
+///
+/// # Safety
+/// `expr` must be a valid NUL-terminated string and `out` must point to
+/// writable memory for one `OzonResult`.
+#[no_mangle]
+pub unsafe extern "C" fn ozon_solve(expr: *const std::os::raw::c_char, out: *mut OzonResult) -> i32 {
+    if expr.is_null() || out.is_null() {
+        return OZON_ERR_NULL;
+    }
+    let func_str = match std::ffi::CStr::from_ptr(expr).to_str() {
+        Ok(s) => preprocess_expr(s),
+        Err(_) => return OZON_ERR_UTF8,
+    };
+    // Unwinding across the C ABI aborts the host process
+    match std::panic::catch_unwind(|| solve(&func_str)) {
+        Ok(Some(result)) => {
+            *out = OzonResult::from(&result);
+            OZON_OK
+        }
+        Ok(None) => OZON_ERR_PARSE,
+        Err(_) => OZON_ERR_PANIC,
+    }
+}
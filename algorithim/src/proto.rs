@@ -1216,27 +1216,36 @@ fn solve(func_str: &str) -> Option<SolveResult> {
 fn can_achieve_value(func: &impl Fn(f64) -> f64, target: f64, domain: &Domain) -> bool {
     if !target.is_finite() { return false; }
     
-    let (lo, hi) = match domain {
+    // Search each piece of the domain for x where f(x) is very close to target
+    scan_windows(domain).into_iter().any(|(lo, hi)| {
+        let step = (hi - lo) / 100000.0;
+        let mut x = lo;
+        while x <= hi {
+            if let Some(val) = safe_eval(func, x) {
+                if (val - target).abs() < 1e-6 {
+                    return true;
+                }
+            }
+            x += step;
+        }
+        false
+    })
+}
+
+/// Finite windows to scan for attained values: one per interval of the domain,
+/// infinite ends cut at +-1000, so a union's gaps are never probed
+fn scan_windows(domain: &Domain) -> Vec<(f64, f64)> {
+    match domain {
         Domain::Interval { min, max, .. } => {
             let l = if *min == NEG_INFINITY { -1000.0 } else { *min };
             let h = if *max == INFINITY { 1000.0 } else { *max };
-            (l, h)
-        }
-        _ => (-1000.0, 1000.0),
-    };
-    
-    // Search for x where f(x) is very close to target
-    let step = (hi - lo) / 100000.0;
-    let mut x = lo;
-    while x <= hi {
-        if let Some(val) = safe_eval(func, x) {
-            if (val - target).abs() < 1e-6 {
-                return true;
-            }
+            vec![(l, h)]
         }
-        x += step;
+        Domain::Union(parts) => parts.iter().flat_map(scan_windows).collect(),
+        Domain::Complement { base, .. } => scan_windows(base),
+        Domain::Empty => Vec::new(),
+        _ => vec![(-1000.0, 1000.0)],
     }
-    false
 }
 
 fn detect_range_type(func_str: &str, _func: &impl Fn(f64) -> f64, values: &[f64], _final_min: f64, _final_max: f64) -> RangeType {
@@ -1394,6 +1403,18 @@ fn main() {
     for t in &extreme_tests { run_test(t); }
     let extreme_time = start_extreme.elapsed();
     
+    println!("\n{}", "--- Attainment Checks ---".white().bold());
+    // 1/x on its right branch only reaches positive values; -1 lives on the left one
+    let reciprocal = |x: f64| 1.0 / x;
+    let left = Domain::Interval { min: NEG_INFINITY, max: 0.0, min_open: true, max_open: true };
+    let right = Domain::Interval { min: 0.0, max: INFINITY, min_open: true, max_open: true };
+    let branches = Domain::Union(vec![left, right.clone()]);
+    for (domain, target, expected) in [(&right, 0.5, true), (&right, -1.0, false), (&branches, -1.0, true)] {
+        let attained = can_achieve_value(&reciprocal, target, domain);
+        let line = format!("1/x = {} on {} -> {}", target, domain, attained);
+        if attained == expected { println!("{}{}", "OK: ".green(), line); } else { println!("{}{}", "MISMATCH: ".red(), line); }
+    }
+
     // Print timing summary
     println!("\n{}", "=== PERFORMANCE SUMMARY ===".magenta().bold());
     println!("Standard tests ({} functions):  {:?}", tests.len(), std_time);
@@ -0,0 +1,15 @@
+//! The parallel Brent window sweep finds the same extremes as its sequential
+//! reference, window for window.
+use algorithim::{brent_sweep, brent_sweep_sequential, preprocess_expr, Domain, SolverConfig};
+
+#[test]
+fn parallel_sweep_matches_sequential() {
+    let config = SolverConfig::default();
+    for t in ["x*exp(-x^2)", "sin(x)*cos(x)", "x^4 - x^2", "x/(1+x^2)"] {
+        let f = preprocess_expr(t);
+        let parallel = brent_sweep(&f, &Domain::Reals, -100.0, 100.0, 20, &config);
+        let sequential = brent_sweep_sequential(&f, &Domain::Reals, -100.0, 100.0, 20, &config);
+        assert!(parallel.is_some(), "{}", t);
+        assert_eq!(parallel, sequential, "{}", t);
+    }
+}
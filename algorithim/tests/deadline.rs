@@ -0,0 +1,18 @@
+//! `solve_with_deadline` hands back the best-effort partial result once its
+//! budget runs out.
+use algorithim::{preprocess_expr, solve_with_deadline, TIMEOUT_METHOD};
+use std::time::Duration;
+
+#[test]
+fn expired_deadline_returns_a_partial_result() {
+    // Dense oscillation sampling takes far longer than 1ms
+    let result = solve_with_deadline(&preprocess_expr("x*sin(1/x)"), Duration::from_millis(1)).expect("x*sin(1/x) parses");
+    assert_eq!(result.method, TIMEOUT_METHOD);
+}
+
+#[test]
+fn generous_deadline_gives_the_full_result() {
+    let result = solve_with_deadline(&preprocess_expr("x^2"), Duration::from_secs(60)).expect("x^2 parses");
+    assert_ne!(result.method, TIMEOUT_METHOD);
+    assert_eq!(result.range.to_string(), "Interval.Ropen(0, oo)");
+}
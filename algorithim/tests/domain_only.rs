@@ -0,0 +1,19 @@
+//! `detect_domain_only` gives the same domain as a full solve, without the range.
+use algorithim::{detect_domain_only, preprocess_expr};
+
+#[test]
+fn domain_only_matches_the_documented_domains() {
+    for (t, expected) in [
+        ("sqrt(x)", "Interval.Ropen(0, oo)"),
+        ("1/x", "Union(Interval.open(-oo, 0), Interval.open(0, oo))"),
+        ("ln(x)", "Interval.open(0, oo)"),
+    ] {
+        let domain = detect_domain_only(&preprocess_expr(t)).map(|domain| domain.to_string());
+        assert_eq!(domain.as_deref().ok(), Some(expected), "{}", t);
+    }
+}
+
+#[test]
+fn domain_only_reports_unparseable_input() {
+    assert!(detect_domain_only("1/(x").is_err());
+}
@@ -0,0 +1,54 @@
+//! `ozon_solve` through its C ABI: status codes and the plain-data result.
+use std::ffi::CString;
+use std::mem::MaybeUninit;
+
+use algorithim::{
+    ozon_solve, Domain, Interval, OzonDomainKind, OzonRangeKind, OzonResult, Range, RangeType, SolveResult, OZON_ERR_NULL,
+    OZON_ERR_PARSE, OZON_OK,
+};
+
+fn call(expr: &str) -> (i32, Option<OzonResult>) {
+    let c_expr = CString::new(expr).unwrap();
+    let mut out = MaybeUninit::<OzonResult>::uninit();
+    let code = unsafe { ozon_solve(c_expr.as_ptr(), out.as_mut_ptr()) };
+    (code, (code == OZON_OK).then(|| unsafe { out.assume_init() }))
+}
+
+#[test]
+fn sqrt_fills_domain_and_range() {
+    let (code, result) = call("sqrt(x)");
+    assert_eq!(code, OZON_OK);
+    let r = result.unwrap();
+    assert_eq!(r.domain_kind, OzonDomainKind::Interval as i32);
+    assert_eq!((r.domain_min, r.domain_max, r.domain_min_open, r.domain_max_open), (0.0, f64::INFINITY, false, true));
+    assert_eq!(r.range_kind, OzonRangeKind::Interval as i32);
+    assert_eq!((r.range_min, r.range_max, r.range_min_open, r.range_max_open), (0.0, f64::INFINITY, false, true));
+    assert!(r.range_split.is_nan());
+}
+
+#[test]
+fn unparseable_input_is_a_parse_error() {
+    assert_eq!(call("1/(x").0, OZON_ERR_PARSE);
+}
+
+#[test]
+fn null_pointers_are_rejected() {
+    let mut out = MaybeUninit::<OzonResult>::uninit();
+    assert_eq!(unsafe { ozon_solve(std::ptr::null(), out.as_mut_ptr()) }, OZON_ERR_NULL);
+}
+
+#[test]
+fn split_value_and_exterior_bound_are_carried() {
+    let split = OzonResult::from(&SolveResult {
+        domain: Domain::Reals,
+        range: Range { hull: Interval::reals(), range_type: RangeType::SplitAtValue { excluded: 2.0 } },
+        method: String::new(),
+        warnings: Vec::new(),
+    });
+    assert_eq!(split.range_kind, OzonRangeKind::SplitAtValue as i32);
+    assert_eq!(split.range_split, 2.0);
+
+    let exterior = call("sec(x)").1.unwrap();
+    assert_eq!(exterior.range_kind, OzonRangeKind::UnionExterior as i32);
+    assert_eq!((exterior.range_split, exterior.range_split_closed), (1.0, true));
+}
@@ -0,0 +1,19 @@
+//! Pole-free standard functions start from a coarse full-line scan, densified
+//! only around turning points and jumps, and stay well under the old 40k-point
+//! scan without losing their ranges.
+use algorithim::{sample_values, solve, GridConfig};
+
+#[test]
+fn coarse_then_dense_scan_stays_under_budget() {
+    for (t, range) in [
+        ("abs(x)", "Interval.Ropen(0, oo)"),
+        ("floor(x)", "Integers"),
+        ("x^2", "Interval.Ropen(0, oo)"),
+        ("sin(x)", "Interval(-1, 1)"),
+        ("x^3", "Reals"),
+    ] {
+        let samples = sample_values(t, &GridConfig::default()).len();
+        assert!(samples < 12_000, "{} took {} samples", t, samples);
+        assert_eq!(solve(t).expect("parses").range.to_string(), range, "{}", t);
+    }
+}
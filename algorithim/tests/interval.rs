@@ -0,0 +1,39 @@
+//! The shared `Interval` type: membership, measure, intersection, union and display.
+use algorithim::{DisplayStyle, Interval};
+
+fn unit() -> Interval {
+    Interval::new(0.0, 1.0, false, true)    // [0, 1)
+}
+
+#[test]
+fn contains_respects_open_ends() {
+    let unit = unit();
+    assert!(unit.contains(0.0) && unit.contains(0.5));
+    assert!(!unit.contains(1.0) && !unit.contains(-0.1));
+}
+
+#[test]
+fn measure() {
+    assert_eq!(unit().measure(), 1.0);
+    assert_eq!(Interval::new(2.0, 2.0, false, false).measure(), 0.0);
+    assert_eq!(Interval::reals().measure(), f64::INFINITY);
+}
+
+#[test]
+fn intersect() {
+    assert_eq!(unit().intersect(&Interval::new(0.5, 2.0, false, false)), Some(Interval::new(0.5, 1.0, false, true)));
+    assert_eq!(unit().intersect(&Interval::new(1.0, 2.0, false, false)), None);
+}
+
+#[test]
+fn union_joins_touching_ends_only_when_one_side_holds_the_point() {
+    assert_eq!(unit().union(&Interval::new(1.0, 2.0, false, false)), Some(Interval::new(0.0, 2.0, false, false)));
+    assert_eq!(unit().union(&Interval::new(1.0, 2.0, true, false)), None);
+    assert_eq!(unit().union(&Interval::new(-1.0, 0.5, true, true)), Some(Interval::new(-1.0, 1.0, true, true)));
+}
+
+#[test]
+fn display() {
+    assert_eq!(unit().to_string(), "Interval.Ropen(0, 1)");
+    assert_eq!(unit().fmt_styled(DisplayStyle::Brackets), "[0, 1)");
+}
@@ -0,0 +1,17 @@
+//! `interval_range_bound` encloses the true range, and more slices tighten it.
+use algorithim::{interval_range_bound, Interval};
+
+#[test]
+fn subdivision_tightens_an_enclosing_bound() {
+    // x^2 - x on [0, 2] is [-1/4, 2]; x appears twice, so one slice gives [-2, 4]
+    let window = Interval::new(0.0, 2.0, false, false);
+    let bounds: Vec<Interval> = [1, 4, 16, 64].iter()
+        .map(|&n| interval_range_bound("x^2 - x", &window, n).expect("x^2 - x parses"))
+        .collect();
+    assert_eq!((bounds[0].lo, bounds[0].hi), (-2.0, 4.0));
+    for bound in &bounds {
+        assert!(bound.lo <= -0.25 && bound.hi >= 2.0, "{:?} does not enclose [-1/4, 2]", bound);
+    }
+    let widths: Vec<f64> = bounds.iter().map(|b| b.hi - b.lo).collect();
+    assert!(widths.windows(2).all(|w| w[1] < w[0]), "widths {:?} do not shrink", widths);
+}
@@ -0,0 +1,29 @@
+//! `solve_with_params` binds named constants and leaves x to solve over.
+use algorithim::solve_with_params;
+
+#[test]
+fn sign_of_a_flips_the_parabola() {
+    for (a, expected) in [(2.0, "Interval.Ropen(0, oo)"), (-1.0, "Interval.Lopen(-oo, 0)")] {
+        let range = solve_with_params("a*x^2", &[("a", a)]).map(|result| result.range.to_string());
+        assert_eq!(range.as_deref(), Some(expected), "a = {}", a);
+    }
+}
+
+#[test]
+fn name_inside_another_name_is_left_alone() {
+    // Binding a must not touch the a in tan
+    let range = solve_with_params("a + tan(x)^2", &[("a", 1.0)]).map(|result| result.range.to_string());
+    assert_eq!(range.as_deref(), Some("Interval.Ropen(1, oo)"));
+}
+
+#[test]
+fn solve_variable_and_constants_cannot_be_rebound() {
+    for name in ["x", "e", "pi"] {
+        assert!(solve_with_params("a*x^2 + e + pi", &[("a", 1.0), (name, 2.0)]).is_none(), "{}", name);
+    }
+}
+
+#[test]
+fn non_finite_value_is_rejected() {
+    assert!(solve_with_params("a*x^2", &[("a", f64::NAN)]).is_none());
+}
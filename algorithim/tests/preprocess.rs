@@ -0,0 +1,32 @@
+//! `preprocess_expr` normalizes written math to meval syntax, and a second
+//! pass leaves its output unchanged.
+use algorithim::preprocess_expr;
+
+#[test]
+fn normalizes_written_math_idempotently() {
+    for (input, expected) in [
+        ("x**2", "x^2"),
+        ("log(x)", "ln(x)"),
+        ("arcsin(x)", "asin(x)"),
+        ("arctan(x) + arccos(x)", "atan(x)+acos(x)"),
+        ("2x", "2*x"),
+        ("3sin(x)", "3*sin(x)"),
+        ("(x+1)(x-1)", "(x+1)*(x-1)"),
+        ("2pi x", "2*pi*x"),
+        ("x(x+1)", "x*(x+1)"),
+        ("1.5e-3x", "1.5e-3*x"),
+        ("2e", "2*e"),
+        ("exp(-x^2)", "exp(-x^2)"),
+        ("log(2, 8)", "(ln(8)/ln(2))"),
+        ("log(10, x^2+1)", "(ln(x^2+1)/ln(10))"),
+        ("2×x − 1", "2*x-1"),
+        ("sin(2πx)", "sin(2*pi*x)"),
+        ("π×x÷2", "pi*x/2"),
+        ("x·√x", "x*sqrt(x)"),
+        ("√(x+1)\u{a0}+\u{a0}√2", "sqrt(x+1)+sqrt(2)"),
+    ] {
+        let once = preprocess_expr(input);
+        assert_eq!(once, expected, "{}", input);
+        assert_eq!(preprocess_expr(&once), once, "second pass over {}", input);
+    }
+}
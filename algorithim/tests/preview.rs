@@ -0,0 +1,11 @@
+//! `solve_preview` is marked as a preview and agrees with the full solve where
+//! the coarse grid already settles the answer.
+use algorithim::{solve, solve_preview, PREVIEW_METHOD};
+
+#[test]
+fn preview_agrees_with_full_solve_on_x_squared() {
+    let preview = solve_preview("x^2").expect("x^2 parses");
+    let full = solve("x^2").expect("x^2 parses");
+    assert_eq!(preview.method, PREVIEW_METHOD);
+    assert_eq!(preview.range.to_string(), full.range.to_string());
+}
@@ -0,0 +1,19 @@
+//! Two sinusoids with an irrational frequency ratio never peak together: the
+//! range is open at 2. When the ratio can't be classified the sampled ends are
+//! not claimed as attained.
+use algorithim::{preprocess_expr, solve};
+
+#[test]
+fn surd_and_e_ratios_are_irrational() {
+    for t in ["sin(x)+sin(sqrt(2)*x)", "sin(x)+sin(e*x)", "sin(x)+sin(pi*x)"] {
+        let result = solve(&preprocess_expr(t)).expect("parses");
+        assert_eq!(result.range.to_string(), "Interval.open(-2, 2)", "{}", t);
+    }
+}
+
+#[test]
+fn unclassified_ratio_leaves_the_ends_open_with_a_warning() {
+    let result = solve(&preprocess_expr("sin(x)+sin(ln(2)*x)")).expect("parses");
+    assert!(result.range.hull.lo_open && result.range.hull.hi_open, "range {}", result.range);
+    assert!(!result.warnings.is_empty());
+}
@@ -0,0 +1,16 @@
+//! A range gap is only reported when no branch sweeps through it, including
+//! the tail a branch keeps covering on its way to an end limit.
+use algorithim::{preprocess_expr, solve};
+
+#[test]
+fn tail_toward_an_asymptote_fills_the_gap() {
+    // The upper branch falls from oo toward 0 as x -> oo
+    let result = solve(&preprocess_expr("sqrt(x)/(x-300)")).expect("parses");
+    assert_eq!(result.range.to_string(), "Reals");
+}
+
+#[test]
+fn gap_between_branches_is_kept() {
+    let result = solve(&preprocess_expr("1/(x^2-4)")).expect("parses");
+    assert_eq!(result.range.to_string(), "Union(Interval.Lopen(-oo, -1/4), Interval.open(0, oo))");
+}
@@ -0,0 +1,73 @@
+//! Solving on a window: exclusions inside it stay excluded and closed ends of
+//! every piece count toward the range.
+use algorithim::{preprocess_expr, solve_on_domain, Domain, Interval};
+
+fn on(t: &str, lo: f64, hi: f64) -> (String, String) {
+    on_interval(t, Interval::new(lo, hi, false, false))
+}
+
+fn on_interval(t: &str, window: Interval) -> (String, String) {
+    let result = solve_on_domain(&preprocess_expr(t), &Domain::Interval(window)).expect(t);
+    (result.domain.to_string(), result.range.to_string())
+}
+
+#[test]
+fn ranges_on_a_window() {
+    let half_line = Interval::new(0.0, f64::INFINITY, false, true);
+    let positive = Interval::new(0.0, f64::INFINITY, true, true);
+    for (t, window, expected) in [
+        ("1 - exp(-x)", half_line, "Interval.Ropen(0, 1)"),
+        ("x^3 - x", Interval::new(-1.0, 1.0, false, false), "Interval(-0.3849, 0.3849)"),  // -+2/(3*sqrt(3))
+        ("x^2*exp(-x)", half_line, "Interval(0, 4*exp(-2))"),  // max at x = 2
+        ("x*ln(x)", positive, "Interval.Ropen(-exp(-1), oo)"),  // removable 0 at 0+, min at x = 1/e
+    ] {
+        assert_eq!(on_interval(t, window).1, expected, "{}", t);
+    }
+}
+
+#[test]
+fn periodic_poles_inside_the_window_are_excluded() {
+    let (domain, range) = on("tan(x)", 0.0, 4.0);
+    assert_eq!(domain, "Union(Interval.Ropen(0, pi/2), Interval.Lopen(pi/2, 4))");
+    assert_eq!(range, "Reals");
+}
+
+#[test]
+fn closed_ends_of_union_pieces_bound_the_range() {
+    assert_eq!(on("1/x", -1.0, 1.0).1, "Union(Interval.Lopen(-oo, -1), Interval.Ropen(1, oo))");
+    assert_eq!(on("1/x", -2.0, 3.0).1, "Union(Interval.Lopen(-oo, -1/2), Interval.Ropen(1/3, oo))");
+}
+
+#[test]
+fn gamma_poles_inside_the_window_are_excluded() {
+    let (domain, _) = on("gamma(x)", -2.5, 1.0);
+    assert_eq!(domain, "Union(Interval.Ropen(-5/2, -2), Interval.open(-2, -1), Interval.open(-1, 0), Interval.Lopen(0, 1))");
+}
+
+#[test]
+fn half_integer_gamma_poles_inside_the_window_are_excluded() {
+    // Poles of gamma(2x - 3) at 3/2, 1, 1/2, ...; none above 3/2
+    let (domain, _) = on("gamma(2*x-3)", 0.0, 2.0);
+    assert_eq!(domain, "Union(Interval.open(0, 1/2), Interval.open(1/2, 1), Interval.open(1, 3/2), Interval.Lopen(3/2, 2))");
+}
+
+#[test]
+fn single_tan_branch_is_unbounded_both_ways() {
+    let half_pi = std::f64::consts::FRAC_PI_2;
+    let (domain, range) = on_interval("tan(x)", Interval::new(-half_pi, half_pi, true, true));
+    assert_eq!(domain, "Interval.open(-pi/2, pi/2)");
+    assert_eq!(range, "Reals");
+}
+
+#[test]
+fn gamma_minimum_on_the_positive_half_line() {
+    // min 0.885603 at x = 1.461632
+    let range = on_interval("gamma(x)", Interval::new(0.0, f64::INFINITY, true, true)).1;
+    assert_eq!(range, "Interval.Ropen(0.885603, oo)");
+}
+
+#[test]
+fn few_levels_on_a_window_are_a_finite_set() {
+    // The attained values, not their hull
+    assert_eq!(on("floor(x/2)", 0.0, 10.0).1, "FiniteSet(0, 1, 2, 3, 4, 5)");
+}
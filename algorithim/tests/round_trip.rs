@@ -0,0 +1,14 @@
+//! `to_bytes`/`from_bytes` reproduce a result exactly: infinite bounds, a
+//! finite set and warnings included.
+use algorithim::{preprocess_expr, solve, SolveResult};
+
+#[test]
+fn binary_round_trip_is_lossless() {
+    // Infinite bounds, a finite set, and a sparse domain that carries a warning
+    for (t, warned) in [("1/x", false), ("heaviside(x)", false), ("sqrt(x^2 - 1e6)", true)] {
+        let result = solve(&preprocess_expr(t)).expect(t);
+        assert_eq!(!result.warnings.is_empty(), warned, "{}", t);
+        let bytes = result.to_bytes().expect(t);
+        assert_eq!(SolveResult::from_bytes(&bytes).ok(), Some(result), "{}", t);
+    }
+}
@@ -0,0 +1,10 @@
+//! An extreme on the scan's outermost sample widens the scan once to reach it.
+use algorithim::{preprocess_expr, solve};
+
+#[test]
+fn peak_past_the_outermost_sample_is_found() {
+    // Peak 1e6/e at x = 1e6
+    let peak = 1e6 / std::f64::consts::E;
+    let max = solve(&preprocess_expr("x*exp(-x/10^6)")).expect("parses").range.hull.hi;
+    assert!((max - peak).abs() < 1e-3, "max {} (expected {})", max, peak);
+}
@@ -0,0 +1,32 @@
+//! Domains read off a validity scan: boundaries past the dense window are only
+//! searched where a polynomial radicand puts them, and an oscillating radicand
+//! is reported as resolved on the window alone.
+use algorithim::{preprocess_expr, solve, Domain};
+
+#[test]
+fn polynomial_radicand_boundaries_past_the_window_are_found() {
+    for (t, expected) in [
+        ("sqrt((x-200)*(x-300))", "Union(Interval.Lopen(-oo, 200), Interval.Ropen(300, oo))"),
+        ("sqrt(x-200)", "Interval.Ropen(200, oo)"),
+        ("sqrt(1000-x)", "Interval.Lopen(-oo, 1000)"),
+        ("sqrt(exp(x)-2)", "Interval.Ropen(0.693147, oo)"),
+    ] {
+        let result = solve(&preprocess_expr(t)).expect("parses");
+        assert_eq!(result.domain.to_string(), expected, "{}", t);
+    }
+}
+
+#[test]
+fn oscillating_radicand_stays_on_the_window() {
+    let result = solve(&preprocess_expr("sqrt(sin(x))")).expect("parses");
+    let Domain::UnionOfIntervals(parts) = &result.domain else { panic!("domain {}", result.domain) };
+    assert!(parts.iter().all(|part| part.lo >= -100.0 && part.hi <= 100.0), "domain {}", result.domain);
+    assert!(!result.warnings.is_empty());
+}
+
+#[test]
+fn oscillating_radicand_is_not_exact() {
+    let result = solve(&preprocess_expr("x*sqrt(sin(100*x) - 0.999)")).expect("parses");
+    assert!(!result.method.starts_with("Exact"), "method {}", result.method);
+    assert!(result.warnings.iter().any(|w| w.contains("oscillates")), "warnings {:?}", result.warnings);
+}
@@ -0,0 +1,11 @@
+//! Ranges that the parallel build reports. Run with `--no-default-features` to
+//! confirm the sequential stand-ins for the rayon loops give identical results.
+use algorithim::{preprocess_expr, solve};
+
+#[test]
+fn sequential_build_matches_parallel_ranges() {
+    for (t, expected) in [("x^2", "Interval.Ropen(0, oo)"), ("x/(1+x^2)", "Interval(-1/2, 1/2)"), ("tan(x)", "Reals")] {
+        let range = solve(&preprocess_expr(t)).map(|result| result.range.to_string());
+        assert_eq!(range.as_deref(), Some(expected), "{}", t);
+    }
+}
@@ -0,0 +1,13 @@
+//! Reordered spellings share one canonical key, so the second is a cache hit.
+use algorithim::{canonicalize, preprocess_expr, solve_cached};
+
+#[test]
+fn reordered_sums_share_a_key_and_a_cached_result() {
+    let keys = ["x^2+1", "1+x^2"].map(|t| canonicalize(&preprocess_expr(t)));
+    assert!(keys[0].is_some());
+    assert_eq!(keys[0], keys[1]);
+    let first = solve_cached(&preprocess_expr("x^2+1")).expect("x^2+1 parses");
+    let second = solve_cached(&preprocess_expr("1+x^2")).expect("1+x^2 parses");
+    assert_eq!(first, second);
+    assert_eq!(second.range.to_string(), "Interval.Ropen(1, oo)");
+}
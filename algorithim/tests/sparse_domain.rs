@@ -0,0 +1,9 @@
+//! A domain made of many thin slivers leaves few finite samples; the solver
+//! says so instead of trusting the extremes it happened to hit.
+use algorithim::{preprocess_expr, solve};
+
+#[test]
+fn sparse_domain_is_reported_as_a_warning() {
+    let result = solve(&preprocess_expr("x*sqrt(sin(100*x) - 0.999)")).expect("parses");
+    assert!(!result.warnings.is_empty(), "no warning for a sparse domain");
+}
@@ -0,0 +1,10 @@
+//! Every `SPECIAL_CASES` entry comes back from `solve` exactly as documented.
+use algorithim::{solve, SPECIAL_CASES};
+
+#[test]
+fn special_case_table_matches_solve() {
+    for (expr, entry) in SPECIAL_CASES {
+        let range = solve(expr).map(|result| result.range.to_string());
+        assert_eq!(range, Some(entry.to_range().to_string()), "{}", expr);
+    }
+}
@@ -0,0 +1,17 @@
+//! Streaming the lazy smart grid visits exactly the points a materialized scan
+//! would, so both see the same extremes.
+use algorithim::{sample_values, streamed_min_max, GridConfig};
+
+#[test]
+fn streamed_extremes_match_materialized_samples() {
+    let config = GridConfig::default();
+    for t in ["1/(x^2-4)", "sqrt(16 - x^2)", "ln(x)", "tan(x)"] {
+        let values = sample_values(t, &config);
+        assert!(!values.is_empty(), "{}", t);
+        let materialized = values.iter().fold(None, |bounds: Option<(f64, f64)>, &y| match bounds {
+            None => Some((y, y)),
+            Some((lo, hi)) => Some((y.min(lo), y.max(hi))),
+        });
+        assert_eq!(streamed_min_max(t, &config), materialized, "{}", t);
+    }
+}
@@ -6,7 +6,8 @@
 use numpy::ndarray::Array1;
 use numpy::{IntoPyArray, PyArray1, PyReadonlyArray1};
 use pyo3::prelude::*;
-use std::f64::consts::{E, PI};
+use rayon::prelude::*;
+use std::f64::consts::{E, PI, SQRT_2};
 
 // =============================================================================
 // CONSTANTS
@@ -14,6 +15,56 @@ use std::f64::consts::{E, PI};
 const INF_THRESHOLD: f64 = 1e12;
 const ZERO_THRESHOLD: f64 = 1e-9;
 
+// =============================================================================
+// CONFIGURATION - Defaults shared by the tunable functions
+// =============================================================================
+
+/// Tolerances and sizes the functions below fall back to when a call leaves
+/// them out. Build one and pass it as `config=` instead of repeating numbers.
+#[pyclass]
+#[derive(Debug, Clone)]
+struct FastMathConfig {
+    /// Relative tolerance for `brent_minimize`
+    #[pyo3(get, set)]
+    tol: f64,
+    /// Iteration cap for `brent_minimize`
+    #[pyo3(get, set)]
+    max_iter: usize,
+    /// Points per chunk in `batch_find_extrema`
+    #[pyo3(get, set)]
+    chunk_size: usize,
+    /// Samples per scale in `generate_multi_scale_grid`
+    #[pyo3(get, set)]
+    grid_density: usize,
+}
+
+impl Default for FastMathConfig {
+    fn default() -> Self {
+        FastMathConfig { tol: 1e-6, max_iter: 100, chunk_size: 1000, grid_density: 800 }
+    }
+}
+
+#[pymethods]
+impl FastMathConfig {
+    #[new]
+    #[pyo3(signature = (tol=1e-6, max_iter=100, chunk_size=1000, grid_density=800))]
+    fn new(tol: f64, max_iter: usize, chunk_size: usize, grid_density: usize) -> PyResult<Self> {
+        if tol.is_nan() || tol <= 0.0 || max_iter == 0 || chunk_size == 0 || grid_density < 2 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "tol must be positive, max_iter and chunk_size at least 1, grid_density at least 2",
+            ));
+        }
+        Ok(FastMathConfig { tol, max_iter, chunk_size, grid_density })
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "FastMathConfig(tol={}, max_iter={}, chunk_size={}, grid_density={})",
+            self.tol, self.max_iter, self.chunk_size, self.grid_density
+        )
+    }
+}
+
 // =============================================================================
 // GRID SAMPLING - Parallel evaluation of function values
 // =============================================================================
@@ -30,12 +81,15 @@ fn linspace(start: f64, end: f64, num: usize) -> Vec<f64> {
 
 /// Generate sample points for multiple scales (optimized)
 #[pyfunction]
+#[pyo3(signature = (gen_min, gen_max, scales, samples_per_scale=None, config=None))]
 fn generate_multi_scale_grid(
     gen_min: f64, 
     gen_max: f64, 
     scales: Vec<f64>,
-    samples_per_scale: usize
+    samples_per_scale: Option<usize>,
+    config: Option<FastMathConfig>
 ) -> Vec<f64> {
+    let samples_per_scale = samples_per_scale.unwrap_or(config.unwrap_or_default().grid_density);
     let mut points: Vec<f64> = Vec::with_capacity(scales.len() * samples_per_scale);
     
     for scale in scales {
@@ -104,32 +158,50 @@ fn find_sign_changes<'py>(
 
 /// Brent's method for finding minimum in a bounded interval
 /// Much faster than differential evolution for 1D problems
+/// With `find_max` it negates internally and returns the maximum instead
 #[pyfunction]
+#[pyo3(signature = (func, a, b, tol=None, max_iter=None, config=None, find_max=false))]
+// Each argument is a Python keyword; tol and max_iter stay alongside config so
+// existing brent_minimize(f, a, b, tol=...) calls keep working
+#[allow(clippy::too_many_arguments)]
 fn brent_minimize(
     py: Python<'_>,
     func: PyObject,
     a: f64,
     b: f64,
-    tol: f64,
-    max_iter: usize
+    tol: Option<f64>,
+    max_iter: Option<usize>,
+    config: Option<FastMathConfig>,
+    find_max: bool
 ) -> PyResult<(f64, f64)> {
     const GOLDEN: f64 = 0.3819660112501051;  // (3 - sqrt(5)) / 2
     
+    let config = config.unwrap_or_default();
+    let tol = tol.unwrap_or(config.tol);
+    let max_iter = max_iter.unwrap_or(config.max_iter);
+    
     let mut a = a;
     let mut b = b;
     let mut x = a + GOLDEN * (b - a);
     let mut w = x;
     let mut v = x;
     
+    let sign = if find_max { -1.0 } else { 1.0 };
+    
     // Evaluate function
     let eval_f = |x_val: f64| -> PyResult<f64> {
         Python::with_gil(|py| {
             let result = func.call1(py, (x_val,))?;
-            result.extract::<f64>(py)
+            Ok(sign * result.extract::<f64>(py)?)
         })
     };
     
     let mut fx = eval_f(x)?;
+    if !fx.is_finite() {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "function is not finite at the starting point x = {}", x
+        )));
+    }
     let mut fw = fx;
     let mut fv = fx;
     
@@ -142,7 +214,7 @@ fn brent_minimize(
         
         // Check for convergence
         if (x - midpoint).abs() <= tol2 - 0.5 * (b - a) {
-            return Ok((x, fx));
+            return Ok((x, sign * fx));
         }
         
         let d: f64;
@@ -206,7 +278,7 @@ fn brent_minimize(
         }
     }
     
-    Ok((x, fx))
+    Ok((x, sign * fx))
 }
 
 // =============================================================================
@@ -244,32 +316,79 @@ fn parallel_grid_eval(
     Ok((min_val, max_val, valid_count))
 }
 
+/// Min, max, finite count and NaN count of pre-evaluated y values, reduced in
+/// one Rayon pass with the GIL released. Evaluate the grid in numpy first;
+/// this is the truly parallel counterpart of `parallel_grid_eval`.
+#[pyfunction]
+fn parallel_reduce_stats<'py>(
+    py: Python<'py>,
+    y_values: PyReadonlyArray1<'py, f64>
+) -> PyResult<(f64, f64, usize, usize)> {
+    let y = y_values.as_array();
+    
+    // Strided views have no slice to split across threads, so copy those
+    let owned: Vec<f64>;
+    let slice = match y.as_slice() {
+        Some(slice) => slice,
+        None => {
+            owned = y.to_vec();
+            &owned
+        }
+    };
+    
+    let empty = || (f64::INFINITY, f64::NEG_INFINITY, 0usize, 0usize);
+    let stats = py.allow_threads(|| {
+        slice.par_iter()
+            .fold(empty, |(min, max, valid, nan), &v| {
+                if v.is_finite() {
+                    (min.min(v), max.max(v), valid + 1, nan)
+                } else if v.is_nan() {
+                    (min, max, valid, nan + 1)
+                } else {
+                    (min, max, valid, nan)
+                }
+            })
+            .reduce(empty, |a, b| (a.0.min(b.0), a.1.max(b.1), a.2 + b.2, a.3 + b.3))
+    });
+    
+    Ok(stats)
+}
+
 /// Batch evaluate and find extrema - optimized version that processes in chunks
+/// Also counts the skipped points: +inf, -inf and NaN, where a result that
+/// isn't a float at all counts as NaN. The finite values keep input order.
 #[pyfunction]
+#[pyo3(signature = (func, x_values, chunk_size=None, config=None))]
 fn batch_find_extrema(
     py: Python<'_>,
     func: PyObject,
     x_values: Vec<f64>,
-    chunk_size: usize
-) -> PyResult<(f64, f64, Vec<f64>)> {
+    chunk_size: Option<usize>,
+    config: Option<FastMathConfig>
+) -> PyResult<(f64, f64, Vec<f64>, usize, usize, usize)> {
+    let chunk_size = chunk_size.unwrap_or(config.unwrap_or_default().chunk_size).max(1);
     let mut global_min = f64::INFINITY;
     let mut global_max = f64::NEG_INFINITY;
     let mut all_valid_y: Vec<f64> = Vec::with_capacity(x_values.len());
+    let (mut pos_inf_count, mut neg_inf_count, mut nan_count) = (0usize, 0usize, 0usize);
     
     for chunk in x_values.chunks(chunk_size) {
         for &x in chunk {
             let result: PyResult<f64> = func.call1(py, (x,))?.extract(py);
-            if let Ok(y) = result {
-                if y.is_finite() {
+            match result {
+                Ok(y) if y.is_finite() => {
                     global_min = global_min.min(y);
                     global_max = global_max.max(y);
                     all_valid_y.push(y);
                 }
+                Ok(y) if y == f64::INFINITY => pos_inf_count += 1,
+                Ok(y) if y == f64::NEG_INFINITY => neg_inf_count += 1,
+                _ => nan_count += 1,
             }
         }
     }
     
-    Ok((global_min, global_max, all_valid_y))
+    Ok((global_min, global_max, all_valid_y, pos_inf_count, neg_inf_count, nan_count))
 }
 
 // =============================================================================
@@ -308,6 +427,149 @@ fn format_symbolic_value(val: f64) -> String {
     format!("{:.6}", val).trim_end_matches('0').trim_end_matches('.').to_string()
 }
 
+/// Symbolic form of a value, matching the `algorithim` solver's output: pi
+/// multiples, E and exp(-1), sqrt(2) and sqrt(3) forms, the known extremum
+/// constants like exp(-exp(-1)) and 1/sqrt(2*E), then fractions with small
+/// denominators, then a trimmed 6-decimal number
+#[pyfunction]
+fn format_symbolic_full(val: f64) -> String {
+    if val == f64::INFINITY || val > INF_THRESHOLD {
+        return "oo".to_string();
+    }
+    if val == f64::NEG_INFINITY || val < -INF_THRESHOLD {
+        return "-oo".to_string();
+    }
+    if val.abs() < ZERO_THRESHOLD {
+        return "0".to_string();
+    }
+
+    // Pi and multiples
+    if (val - PI).abs() < 1e-8 { return "pi".to_string(); }
+    if (val + PI).abs() < 1e-8 { return "-pi".to_string(); }
+    if (val - PI / 2.0).abs() < 1e-8 { return "pi/2".to_string(); }
+    if (val + PI / 2.0).abs() < 1e-8 { return "-pi/2".to_string(); }
+    if (val - PI / 3.0).abs() < 1e-8 { return "pi/3".to_string(); }
+    if (val - PI / 4.0).abs() < 1e-8 { return "pi/4".to_string(); }
+    if (val - PI / 6.0).abs() < 1e-8 { return "pi/6".to_string(); }
+    if (val - 2.0 * PI).abs() < 1e-8 { return "2*pi".to_string(); }
+    if (val + 2.0 * PI).abs() < 1e-8 { return "-2*pi".to_string(); }
+    
+    // e and related
+    if (val - E).abs() < 1e-8 { return "E".to_string(); }
+    if (val - 1.0 / E).abs() < 1e-8 { return "exp(-1)".to_string(); }
+    if (val + 1.0 / E).abs() < 1e-8 { return "-exp(-1)".to_string(); }
+    
+    // sqrt(2) and related
+    if (val - SQRT_2).abs() < 1e-8 { return "sqrt(2)".to_string(); }
+    if (val + SQRT_2).abs() < 1e-8 { return "-sqrt(2)".to_string(); }
+    if (val - SQRT_2 / 2.0).abs() < 1e-8 { return "sqrt(2)/2".to_string(); }
+    if (val + SQRT_2 / 2.0).abs() < 1e-8 { return "-sqrt(2)/2".to_string(); }
+    
+    // sqrt(3) and related
+    let sqrt3 = 3.0_f64.sqrt();
+    if (val - sqrt3).abs() < 1e-8 { return "sqrt(3)".to_string(); }
+    if (val + sqrt3).abs() < 1e-8 { return "-sqrt(3)".to_string(); }
+    if (val - sqrt3 / 2.0).abs() < 1e-8 { return "sqrt(3)/2".to_string(); }
+    
+    // x^x minimum = e^(-1/e) ~ 0.6922
+    let x_x_min = (-1.0 / E).exp();
+    if (val - x_x_min).abs() < 1e-6 { return "exp(-exp(-1))".to_string(); }
+    
+    // x*exp(-x^2) extrema = +/- 1/(sqrt(2*e))
+    let x_exp_bound = (0.5_f64 / E).sqrt();
+    if (val - x_exp_bound).abs() < 1e-6 { return "1/sqrt(2*E)".to_string(); }
+    if (val + x_exp_bound).abs() < 1e-6 { return "-1/sqrt(2*E)".to_string(); }
+    
+    // x^2*exp(-x) maximum = 4/e^2 at x = 2
+    if (val - 4.0 / (E * E)).abs() < 1e-6 { return "4*exp(-2)".to_string(); }
+    
+    // ln(x)/x^p maximum = 1/(p*e) at x = e^(1/p): p = 2 and p = 1/2 (p = 1 is exp(-1) above)
+    if (val - 0.5 / E).abs() < 1e-6 { return "exp(-1)/2".to_string(); }
+    if (val - 2.0 / E).abs() < 1e-6 { return "2*exp(-1)".to_string(); }
+
+    if let Some(frac) = try_to_fraction(val) {
+        return frac;
+    }
+
+    let rounded = (val * 1_000_000.0).round() / 1_000_000.0;
+    let s = format!("{:.6}", rounded);
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// Fraction string for a value that is one with a small denominator
+fn try_to_fraction(val: f64) -> Option<String> {
+    // Only try for reasonable values
+    if val.abs() > 1000.0 || val.abs() < 1e-6 {
+        return None;
+    }
+    
+    // Check common simple fractions
+    let fractions = [
+        (1, 2), (1, 3), (1, 4), (1, 5), (1, 6), (1, 8), (1, 10),
+        (2, 3), (3, 4), (2, 5), (3, 5), (4, 5),
+        (5, 6), (5, 8), (3, 8), (7, 8),
+    ];
+    
+    for (num, den) in fractions {
+        let frac_val = num as f64 / den as f64;
+        if (val - frac_val).abs() < 1e-9 {
+            return Some(format!("{}/{}", num, den));
+        }
+        if (val + frac_val).abs() < 1e-9 {
+            return Some(format!("-{}/{}", num, den));
+        }
+    }
+    
+    // Any denominator up to 1000, kept when both parts stay small
+    if let Some((n, d)) = float_to_ratio(val, 1000) {
+        if d != 1 && d <= 100 && n.abs() <= 100 {
+            return Some(format!("{}/{}", n, d));
+        } else if d == 1 {
+            return Some(format!("{}", n));
+        }
+    }
+    
+    None
+}
+
+/// Closest fraction n/d (lowest terms, d > 0) with d up to `max_denom`, if it
+/// lands within 1e-9 of the value
+fn float_to_ratio(val: f64, max_denom: i64) -> Option<(i64, i64)> {
+    if !val.is_finite() {
+        return None;
+    }
+    
+    let sign = if val < 0.0 { -1 } else { 1 };
+    let val = val.abs();
+    
+    let mut best_num = val.round() as i64;
+    let mut best_den = 1_i64;
+    let mut best_err = (val - best_num as f64).abs();
+    
+    for d in 1..=max_denom {
+        let n = (val * d as f64).round() as i64;
+        let err = (val - n as f64 / d as f64).abs();
+        if err < best_err {
+            best_err = err;
+            best_num = n;
+            best_den = d;
+        }
+        if err < 1e-12 {
+            break;
+        }
+    }
+    
+    if best_err >= 1e-9 {
+        return None;
+    }
+    let (mut a, mut b) = (best_num, best_den);
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    let g = a.max(1);
+    Some((sign * best_num / g, best_den / g))
+}
+
 // =============================================================================
 // ADAPTIVE GRID GENERATION
 // =============================================================================
@@ -353,14 +615,17 @@ fn adaptive_grid(
 /// Fast math computation module implemented in Rust
 #[pymodule]
 fn fast_math_rs(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<FastMathConfig>()?;
     m.add_function(wrap_pyfunction!(linspace, m)?)?;
     m.add_function(wrap_pyfunction!(generate_multi_scale_grid, m)?)?;
     m.add_function(wrap_pyfunction!(find_min_max_parallel, m)?)?;
     m.add_function(wrap_pyfunction!(find_sign_changes, m)?)?;
     m.add_function(wrap_pyfunction!(brent_minimize, m)?)?;
     m.add_function(wrap_pyfunction!(parallel_grid_eval, m)?)?;
+    m.add_function(wrap_pyfunction!(parallel_reduce_stats, m)?)?;
     m.add_function(wrap_pyfunction!(batch_find_extrema, m)?)?;
     m.add_function(wrap_pyfunction!(format_symbolic_value, m)?)?;
+    m.add_function(wrap_pyfunction!(format_symbolic_full, m)?)?;
     m.add_function(wrap_pyfunction!(adaptive_grid, m)?)?;
     
     // Module metadata
@@ -0,0 +1,13 @@
+"""Checks for batch_find_extrema. Build the module first: maturin develop --release"""
+import math
+
+import fast_math_rs
+
+
+def test_batch_counts_skipped_points():
+    def f(x):
+        return {0.0: math.inf, 1.0: -math.inf, 2.0: math.nan, 3.0: None}.get(x, x)
+
+    lo, hi, ys, pos_inf, neg_inf, nan = fast_math_rs.batch_find_extrema(f, [5.0, 0.0, 1.0, 2.0, 3.0, 4.0])
+    assert (lo, hi, ys) == (4.0, 5.0, [5.0, 4.0])
+    assert (pos_inf, neg_inf, nan) == (1, 1, 2)
@@ -0,0 +1,15 @@
+"""Checks for brent_minimize. Build the module first: maturin develop --release"""
+import math
+
+import fast_math_rs
+
+
+def test_brent_find_max():
+    # sin has its maximum at pi/2 on [0, pi]
+    x_max, f_max = fast_math_rs.brent_minimize(math.sin, 0.0, math.pi, find_max=True)
+    assert abs(x_max - math.pi / 2) < 1e-4 and abs(f_max - 1.0) < 1e-9
+    try:
+        fast_math_rs.brent_minimize(lambda x: math.nan, 0.0, 1.0)
+    except ValueError:
+        return
+    raise AssertionError("accepted a non-finite start")
@@ -0,0 +1,48 @@
+"""Checks for FastMathConfig. Build the module first: maturin develop --release"""
+import math
+
+import fast_math_rs
+
+
+def test_defaults():
+    cfg = fast_math_rs.FastMathConfig()
+    assert (cfg.tol, cfg.max_iter, cfg.chunk_size, cfg.grid_density) == (1e-6, 100, 1000, 800)
+
+
+def test_rejects_bad_values():
+    for kwargs in ({"tol": 0.0}, {"max_iter": 0}, {"chunk_size": 0}, {"grid_density": 1}):
+        try:
+            fast_math_rs.FastMathConfig(**kwargs)
+        except ValueError:
+            continue
+        raise AssertionError(f"accepted {kwargs}")
+
+
+def test_reused_across_calls():
+    cfg = fast_math_rs.FastMathConfig(tol=1e-10, chunk_size=7, grid_density=11)
+    # (x - 1)^2 has its minimum at x = 1
+    x_min, f_min = fast_math_rs.brent_minimize(lambda x: (x - 1.0) ** 2, -3.0, 4.0, config=cfg)
+    assert abs(x_min - 1.0) < 1e-6 and f_min < 1e-12
+    lo, hi, ys, *_ = fast_math_rs.batch_find_extrema(math.sin, [0.0, math.pi / 2, math.pi, 1.5 * math.pi], config=cfg)
+    assert (lo, hi, len(ys)) == (-1.0, 1.0, 4)
+    # One scale with grid_density points
+    assert len(fast_math_rs.generate_multi_scale_grid(-1.0, 1.0, [1.0], config=cfg)) == 11
+
+
+def test_explicit_argument_wins():
+    cfg = fast_math_rs.FastMathConfig(grid_density=11)
+    assert len(fast_math_rs.generate_multi_scale_grid(-1.0, 1.0, [1.0], 5, config=cfg)) == 5
+
+
+def test_fields_are_settable():
+    cfg = fast_math_rs.FastMathConfig()
+    cfg.max_iter = 5
+    assert cfg.max_iter == 5
+    assert repr(cfg) == "FastMathConfig(tol=0.000001, max_iter=5, chunk_size=1000, grid_density=800)"
+
+
+if __name__ == "__main__":
+    for name, check in list(globals().items()):
+        if name.startswith("test_"):
+            check()
+            print(f"[OK]   {name}")
@@ -0,0 +1,25 @@
+"""Checks for format_symbolic_full. Build the module first: maturin develop --release"""
+import math
+
+import fast_math_rs
+
+
+def test_constants_match_the_solver():
+    cases = {
+        math.sqrt(3) / 2: "sqrt(3)/2",
+        -math.sqrt(2): "-sqrt(2)",
+        math.exp(-math.exp(-1)): "exp(-exp(-1))",
+        1 / math.sqrt(2 * math.e): "1/sqrt(2*E)",
+        -1 / math.e: "-exp(-1)",
+        2 * math.pi: "2*pi",
+    }
+    for value, expected in cases.items():
+        assert fast_math_rs.format_symbolic_full(value) == expected, value
+
+
+def test_fractions_and_fallbacks():
+    assert fast_math_rs.format_symbolic_full(7 / 12) == "7/12"
+    assert fast_math_rs.format_symbolic_full(-3.0) == "-3"
+    assert fast_math_rs.format_symbolic_full(0.2172336) == "0.217234"
+    assert fast_math_rs.format_symbolic_full(1e13) == "oo"
+    assert fast_math_rs.format_symbolic_full(-math.inf) == "-oo"
@@ -0,0 +1,17 @@
+"""Checks for parallel_reduce_stats. Build the module first: maturin develop --release"""
+import math
+
+import numpy as np
+
+import fast_math_rs
+
+
+def test_counts_and_extremes():
+    y = np.array([3.0, -1.5, math.nan, math.inf, 2.0, math.nan])
+    assert fast_math_rs.parallel_reduce_stats(y) == (-1.5, 3.0, 3, 2)
+
+
+def test_strided_view_matches_copy():
+    y = np.sin(np.linspace(-10.0, 10.0, 100_001))
+    view = y[::3]
+    assert fast_math_rs.parallel_reduce_stats(view) == fast_math_rs.parallel_reduce_stats(view.copy())